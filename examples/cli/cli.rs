@@ -71,8 +71,8 @@ fn log_root_arg(cli: Res<Cli>) {
     }
 }
 
-fn log_path_flag(cli: Res<Cli>) {
-    if let Some(path) = &cli.path {
+fn log_path_flag(args: Res<GlobalArgs>) {
+    if let Some(path) = &args.path {
         info!("path flag: {:?}", path);
     }
 }
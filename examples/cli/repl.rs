@@ -0,0 +1,67 @@
+use dip::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugin(CliPlugin::<NoAsyncAction>::repl().with_prompt("todo> "))
+        .add_plugin(ActionPlugin)
+        .add_plugin(TodoActionPlugin)
+        .init_resource::<Todos>()
+        .add_system(handle_ping)
+        .add_system(handle_hello)
+        .add_system(handle_add_todo)
+        .add_system(handle_list_todo)
+        .run();
+}
+
+#[derive(CliPlugin, clap::Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    action: Action,
+}
+
+#[derive(SubcommandPlugin, clap::Subcommand, Clone)]
+pub enum Action {
+    Ping,
+    Hello {
+        name: Option<String>,
+    },
+    #[clap(subcommand)]
+    Todo(TodoAction),
+}
+
+#[derive(SubcommandPlugin, clap::Subcommand, Clone, Debug)]
+pub enum TodoAction {
+    List,
+    Add { name: String },
+}
+
+fn handle_ping(mut events: EventReader<PingAction>) {
+    for _ in events.iter() {
+        println!("Pong !");
+    }
+}
+
+fn handle_hello(mut events: EventReader<HelloAction>) {
+    for e in events.iter() {
+        println!("Hello, {}!", e.name.clone().unwrap_or("world".to_string()));
+    }
+}
+
+// State is kept between commands
+#[derive(Default)]
+struct Todos(Vec<String>);
+
+fn handle_add_todo(mut events: EventReader<AddTodoAction>, mut todos: ResMut<Todos>) {
+    for e in events.iter() {
+        todos.0.push(e.name.clone());
+    }
+}
+
+fn handle_list_todo(mut events: EventReader<ListTodoAction>, todos: Res<Todos>) {
+    for _ in events.iter() {
+        for (i, name) in todos.0.iter().enumerate() {
+            println!("{i}: {name}");
+        }
+    }
+}
@@ -11,6 +11,7 @@ fn main() {
         .add_system(handle_get_ip_address)
         .add_system(handle_get_ip_address_wrongly)
         .add_system(handle_get_user_agent)
+        .add_system(handle_async_action_failed)
         .run();
 }
 
@@ -123,6 +124,12 @@ fn handle_get_user_agent(
     }
 }
 
+fn handle_async_action_failed(mut events: EventReader<AsyncActionFailed>) {
+    for e in events.iter() {
+        error!("AsyncAction {} failed: {}", e.id, e.error);
+    }
+}
+
 #[allow(non_snake_case)]
 fn Root(cx: Scope) -> Element {
     let ip_address = use_read(&cx, IP_ADDRESS);
@@ -0,0 +1,42 @@
+use dip::{bevy::utils::Duration, prelude::*};
+
+/// Render app without window and assert on rendered edits. Useful to test apps on CI.
+fn main() {
+    let mut app = App::new();
+    app.add_plugin(HeadlessDesktopPlugin::<UiState, NoUiAction, NoAsyncAction>::new(Root))
+        .add_plugin(UiStatePlugin);
+
+    let window = app.world.resource::<HeadlessWindow>().clone();
+
+    assert!(window.update_until_rendered(&mut app, Duration::from_secs(1)));
+    assert!(window.edits().iter().any(|edit| edit.contains("count: 0")));
+
+    // mutate ECS state then wait for VirtualDom to apply changes
+    app.world.resource_mut::<Count>().value = 5;
+    assert!(window.update_until_rendered(&mut app, Duration::from_secs(1)));
+    assert!(window
+        .take_edits()
+        .iter()
+        .any(|edit| edit.contains("count: 5")));
+
+    println!("Rendered without window");
+}
+
+#[allow(non_snake_case)]
+fn Root(cx: Scope) -> Element {
+    let count = use_read(&cx, COUNT);
+
+    cx.render(rsx! {
+        p { "count: {count.value}" }
+    })
+}
+
+#[ui_state]
+struct UiState {
+    count: Count,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Count {
+    value: u32,
+}
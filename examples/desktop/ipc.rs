@@ -0,0 +1,76 @@
+use dip::{
+    bevy::{log::LogPlugin, window::WindowId},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+/// Call Rust from JavaScript and JavaScript from Rust
+fn main() {
+    App::new()
+        .add_plugin(LogPlugin)
+        .add_plugin(
+            DesktopPlugin::<NoUiState, UiAction, NoAsyncAction>::new(Root).ipc_handler(
+                "greet",
+                |req: GreetRequest| {
+                    Ok::<_, String>(GreetResponse {
+                        message: format!("Hello, {} !", req.name),
+                    })
+                },
+            ),
+        )
+        .add_event::<UiAction>()
+        .add_system(request_user_agent)
+        .add_system(log_eval_response)
+        .run();
+}
+
+#[allow(non_snake_case)]
+fn Root(cx: Scope) -> Element {
+    let window = use_window::<UiAction, NoAsyncAction>(&cx);
+    let eval = use_eval::<UiAction, NoAsyncAction, _>(&cx);
+
+    cx.render(rsx! {
+        h1 { "IPC Example" }
+        button {
+            onclick: move |_| eval(r#"
+                window.dip.invoke("greet", { name: "dip" })
+                    .then((res) => document.querySelector("h1").innerText = res.message)
+            "#),
+            "Call Rust"
+        }
+        button {
+            onclick: move |_| window.send(UiAction::UserAgent),
+            "Call JavaScript"
+        }
+    })
+}
+
+#[derive(Deserialize)]
+struct GreetRequest {
+    name: String,
+}
+
+#[derive(Serialize)]
+struct GreetResponse {
+    message: String,
+}
+
+#[derive(Clone, Debug)]
+enum UiAction {
+    UserAgent,
+}
+
+fn request_user_agent(mut events: EventReader<UiAction>, mut eval: EventWriter<EvalRequest>) {
+    for _ in events.iter() {
+        eval.send(EvalRequest::new(
+            WindowId::primary(),
+            "return navigator.userAgent",
+        ));
+    }
+}
+
+fn log_eval_response(mut events: EventReader<EvalResponse>) {
+    for response in events.iter() {
+        info!("{}: {:?}", response.id, response.parse::<String>());
+    }
+}
@@ -0,0 +1,69 @@
+use dip::{
+    bevy::{log::LogPlugin, window::WindowDescriptor},
+    prelude::*,
+};
+
+/// Render different root components in each window
+fn main() {
+    App::new()
+        .add_plugin(LogPlugin)
+        .add_plugin(
+            DesktopPlugin::<NoUiState, UiAction, NoAsyncAction>::new(Root)
+                .add_root("preferences", Preferences),
+        )
+        .add_event::<UiAction>()
+        .add_system(open_preferences)
+        .run();
+}
+
+#[allow(non_snake_case)]
+fn Root(cx: Scope) -> Element {
+    let window = use_window::<UiAction, NoAsyncAction>(&cx);
+
+    cx.render(rsx! {
+        h1 { "Main Window" }
+        button {
+            onclick: |_| window.send(UiAction::OpenPreferences),
+            "Open Preferences"
+        }
+    })
+}
+
+#[allow(non_snake_case)]
+fn Preferences(cx: Scope<PreferencesProps>) -> Element {
+    cx.render(rsx! {
+        h1 { "Preferences" }
+        p { "{cx.props.message}" }
+    })
+}
+
+#[derive(Props, PartialEq, Clone, Default)]
+struct PreferencesProps {
+    message: String,
+}
+
+#[derive(Clone, Debug)]
+enum UiAction {
+    OpenPreferences,
+}
+
+fn open_preferences(mut events: EventReader<UiAction>, mut create: EventWriter<CreateRootWindow>) {
+    for action in events.iter() {
+        match action {
+            UiAction::OpenPreferences => {
+                create.send(
+                    CreateRootWindow::new(
+                        "preferences",
+                        WindowDescriptor {
+                            title: "Preferences".to_string(),
+                            ..Default::default()
+                        },
+                    )
+                    .with_props(PreferencesProps {
+                        message: "Opened from main window".to_string(),
+                    }),
+                );
+            }
+        }
+    }
+}
@@ -6,44 +6,55 @@ use dip::{
     prelude::*,
 };
 
-/// This example attemps to create a second window then warning shows up
+/// Each window renders its own VirtualDom. Closing one window keeps the others alive.
 fn main() {
     App::new()
         .add_plugin(LogPlugin)
-        .add_plugin(DesktopPlugin::<NoUiState, NewWindow, NoAsyncAction>::new(
+        .add_plugin(DesktopPlugin::<NoUiState, UiAction, NoAsyncAction>::new(
             Root,
         ))
-        .add_event::<NewWindow>()
+        .add_event::<UiAction>()
         .add_system(create_new_window)
         .run();
 }
 
 #[allow(non_snake_case)]
 fn Root(cx: Scope) -> Element {
-    let window = use_window::<NewWindow, NoAsyncAction>(&cx);
+    let window = use_window::<UiAction, NoAsyncAction>(&cx);
+    let id = window.window_id();
 
     cx.render(rsx! {
-        h1 { "Multiple Windows isn't supported yet!" }
-        p { "Currently Dioxus's VirtualDom doesn't support multi-windows. Please be patient." }
+        h1 { "Window: {id:?}" }
         button {
             onclick: |_| {
-                window.send(NewWindow);
+                window.send(UiAction::NewWindow);
             },
             "New Window"
         }
-        p { "You'll see warning log" }
+        button {
+            onclick: |_| window.close(),
+            "Close"
+        }
     })
 }
 
 #[derive(Clone, Debug)]
-struct NewWindow;
+enum UiAction {
+    NewWindow,
+}
 
-fn create_new_window(mut events: EventReader<NewWindow>, mut create: EventWriter<CreateWindow>) {
-    for _ in events.iter() {
-        let id = WindowId::new();
-        create.send(CreateWindow {
-            id,
-            descriptor: WindowDescriptor::default(),
-        });
+fn create_new_window(mut events: EventReader<UiAction>, mut create: EventWriter<CreateWindow>) {
+    for action in events.iter() {
+        match action {
+            UiAction::NewWindow => {
+                create.send(CreateWindow {
+                    id: WindowId::new(),
+                    descriptor: WindowDescriptor {
+                        title: "New Window".to_string(),
+                        ..Default::default()
+                    },
+                });
+            }
+        }
     }
 }
@@ -0,0 +1,159 @@
+//! `config` subcommand to inspect ConfigPlugin
+
+use crate::exit::AppExitCode;
+use bevy::{
+    app::{App, Plugin},
+    ecs::{
+        event::{EventReader, EventWriter},
+        system::Res,
+    },
+};
+use config::builder::{ConfigBuilder, DefaultState};
+use dip_core::{
+    config::{documented_default, effective_entries, schema, ConfigFiles, ConfigOverrides},
+    schedule::DipStage,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+/// Subcommand to inspect config. Add it to your subcommand enum with full path, so that
+/// handler generated by `SubcommandPlugin` is ordered before [`handle_config_command`].
+///
+/// ```
+/// #[derive(clap::Subcommand, Clone)]
+/// pub enum Action {
+///     #[clap(subcommand)]
+///     Config(dip_cli::config::ConfigCommand),
+/// }
+/// ```
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum ConfigCommand {
+    /// Print effective config and where each value came from
+    Show,
+    /// Print config files in the order of priority
+    Path,
+    /// Print JSON Schema of the config
+    Schema,
+    /// Print default config as TOML with doc comment of each field
+    Default,
+}
+
+/// JSON Schema printed by `config schema`
+#[derive(Debug, Clone)]
+pub struct ConfigSchema(pub Value);
+
+/// Documented TOML printed by `config default`
+#[derive(Debug, Clone)]
+pub struct ConfigDefault(pub String);
+
+/// Handle [`ConfigCommand`] for config type `C`. `config default` prints `C::default()`.
+pub struct ConfigCommandPlugin<C> {
+    config_type: PhantomData<fn() -> C>,
+}
+
+impl<C> ConfigCommandPlugin<C> {
+    /// Create plugin
+    pub fn new() -> Self {
+        Self {
+            config_type: PhantomData,
+        }
+    }
+}
+
+impl<C> Default for ConfigCommandPlugin<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Plugin for ConfigCommandPlugin<C>
+where
+    C: JsonSchema + Serialize + Default + 'static,
+{
+    fn build(&self, app: &mut App) {
+        let default =
+            documented_default(&C::default()).unwrap_or_else(|e| panic!("{}", e.report()));
+
+        app.add_event::<ConfigCommand>()
+            .insert_resource(ConfigSchema(schema::<C>()))
+            .insert_resource(ConfigDefault(default))
+            .add_system_to_stage(DipStage::Action, handle_config_command);
+    }
+}
+
+/// Print config information requested by [`ConfigCommand`]
+pub fn handle_config_command(
+    mut events: EventReader<ConfigCommand>,
+    builder: Option<Res<ConfigBuilder<DefaultState>>>,
+    overrides: Option<Res<ConfigOverrides>>,
+    files: Option<Res<ConfigFiles>>,
+    schema: Option<Res<ConfigSchema>>,
+    default: Option<Res<ConfigDefault>>,
+    mut exit_code: EventWriter<AppExitCode>,
+) {
+    for command in events.iter() {
+        match command {
+            ConfigCommand::Show => {
+                let builder = match &builder {
+                    Some(builder) => builder,
+                    None => {
+                        eprintln!("error: ConfigPlugin is not added");
+                        exit_code.send(AppExitCode::FAILURE);
+                        continue;
+                    }
+                };
+
+                match effective_entries(builder, overrides.as_deref()) {
+                    Ok(entries) => {
+                        let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+                        for entry in entries {
+                            println!(
+                                "{key:width$} = {value}  # {origin}",
+                                key = entry.key,
+                                value = entry.value,
+                                origin = entry.origin,
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e.report());
+                        exit_code.send(AppExitCode::FAILURE);
+                    }
+                }
+            }
+            ConfigCommand::Path => match &files {
+                Some(files) => {
+                    println!("search paths (lowest priority first):");
+                    for name in files.names.iter() {
+                        println!("  {name}");
+                    }
+                    println!("loaded:");
+                    for path in files.loaded() {
+                        println!("  {}", path.display());
+                    }
+                    println!("writable: {}", files.writable_path().display());
+                }
+                None => {
+                    eprintln!("error: ConfigPlugin is not added");
+                    exit_code.send(AppExitCode::FAILURE);
+                }
+            },
+            ConfigCommand::Schema => match &schema {
+                Some(schema) => println!("{}", serde_json::to_string_pretty(&schema.0).unwrap()),
+                None => {
+                    eprintln!("error: ConfigCommandPlugin is not added");
+                    exit_code.send(AppExitCode::FAILURE);
+                }
+            },
+            ConfigCommand::Default => match &default {
+                Some(default) => print!("{}", default.0),
+                None => {
+                    eprintln!("error: ConfigCommandPlugin is not added");
+                    exit_code.send(AppExitCode::FAILURE);
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Exit CLI app with exit code
+
+use bevy::{
+    app::AppExit,
+    ecs::{
+        event::{EventReader, EventWriter},
+        system::ResMut,
+    },
+};
+
+/// Send this event to exit app with the exit code. Also stored as resource so that runner can
+/// pass it to [`std::process::exit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppExitCode(pub i32);
+
+impl AppExitCode {
+    /// Exit code for successful run
+    pub const SUCCESS: Self = Self(0);
+    /// Exit code for general failure
+    pub const FAILURE: Self = Self(1);
+}
+
+/// Record exit code and request [`AppExit`]
+pub fn handle_app_exit_code(
+    mut events: EventReader<AppExitCode>,
+    mut exit_code: ResMut<AppExitCode>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if let Some(code) = events.iter().last() {
+        *exit_code = *code;
+        app_exit.send(AppExit);
+    }
+}
@@ -0,0 +1,71 @@
+//! Shell completions and man page generated from clap definition
+
+use clap::{value_parser, Arg, Command};
+use std::io::{self, Write};
+
+pub use clap_complete::Shell;
+
+/// Write completion script of the command for the shell
+pub fn completions(command: &mut Command, shell: Shell, out: &mut dyn Write) {
+    let bin_name = command.get_name().to_string();
+    clap_complete::generate(shell, command, bin_name, out);
+}
+
+/// Write man page of the command in roff format
+pub fn man(command: Command, out: &mut dyn Write) -> io::Result<()> {
+    clap_mangen::Man::new(command).render(out)
+}
+
+/// Print completions or man page and exit process when app is called with hidden
+/// `completions <SHELL>` or `man` subcommand. Subcommands defined by the app itself take
+/// precedence.
+///
+/// ```sh
+/// my-app completions zsh > _my-app
+/// my-app man > my-app.1
+/// ```
+pub fn run_generators(command: Command, args: impl IntoIterator<Item = String>) {
+    match generate(command, args) {
+        Some(Ok(())) => std::process::exit(0),
+        Some(Err(e)) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        None => {}
+    }
+}
+
+// Print completions or man page to stdout. Returns `None` when args are not a generator
+// subcommand.
+pub(crate) fn generate(
+    mut command: Command,
+    args: impl IntoIterator<Item = String>,
+) -> Option<io::Result<()>> {
+    let generators = Command::new(command.get_name().to_string())
+        .subcommand(
+            Command::new("completions").arg(
+                Arg::new("shell")
+                    .required(true)
+                    .value_parser(value_parser!(Shell)),
+            ),
+        )
+        .subcommand(Command::new("man"));
+
+    let matches = match generators.try_get_matches_from(args) {
+        Ok(matches) => matches,
+        Err(_) => return None,
+    };
+
+    let mut stdout = io::stdout();
+    match matches.subcommand() {
+        Some(("completions", matches)) if command.find_subcommand("completions").is_none() => {
+            let shell = *matches.get_one::<Shell>("shell").unwrap();
+            completions(&mut command, shell, &mut stdout);
+            Some(Ok(()))
+        }
+        Some(("man", _)) if command.find_subcommand("man").is_none() => {
+            Some(man(command, &mut stdout))
+        }
+        _ => None,
+    }
+}
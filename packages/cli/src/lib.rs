@@ -1,5 +1,17 @@
+pub mod config;
+pub mod exit;
+pub mod generate;
+pub mod repl;
+pub mod runner;
+pub mod tool;
+
+pub use crate::exit::AppExitCode;
 pub use dip_macro::{CliPlugin, SubcommandPlugin};
 
 pub mod prelude {
+    pub use crate::{
+        config::{ConfigCommand, ConfigCommandPlugin},
+        exit::AppExitCode,
+    };
     pub use dip_macro::{CliPlugin, SubcommandPlugin};
 }
@@ -0,0 +1,226 @@
+//! Interactive shell for CliPlugin
+
+use crate::generate;
+use bevy::ecs::world::World;
+use clap::Command;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+use std::{path::PathBuf, sync::mpsc as std_mpsc};
+use tokio::sync::mpsc;
+
+type ParseFn = Box<dyn FnMut(&mut World, Vec<String>) -> Result<(), clap::Error>>;
+
+/// Reads commands from stdin and inserts parsed CLI into the world. Used by
+/// [`run_repl`](crate::runner::run_repl).
+pub struct Repl {
+    command: Command<'static>,
+    prompt: String,
+    history: Option<PathBuf>,
+    parse: ParseFn,
+}
+
+impl Repl {
+    /// Create REPL for the clap command. `parse` receives each line split into words, prefixed
+    /// with binary name.
+    pub fn new<F>(command: Command<'static>, parse: F) -> Self
+    where
+        F: FnMut(&mut World, Vec<String>) -> Result<(), clap::Error> + 'static,
+    {
+        let prompt = format!("{}> ", command.get_name());
+
+        Self {
+            command,
+            prompt,
+            history: None,
+            parse: Box::new(parse),
+        }
+    }
+
+    /// Change prompt. Defaults to `"{bin_name}> "`.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Load history from file on start and save each entered line to it. History only lasts
+    /// for the session by default.
+    pub fn history(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history = Some(path.into());
+        self
+    }
+
+    /// Parse a line and insert the result into the world
+    pub(crate) fn dispatch(&mut self, world: &mut World, line: &str) -> ReplInput {
+        let line = line.trim();
+        match line {
+            "" => return ReplInput::Empty,
+            "exit" | "quit" => return ReplInput::Exit,
+            _ => {}
+        }
+
+        let words = match shlex::split(line) {
+            Some(words) => words,
+            None => {
+                eprintln!("error: unterminated quote");
+                return ReplInput::Empty;
+            }
+        };
+        let args: Vec<String> = std::iter::once(self.command.get_name().to_string())
+            .chain(words)
+            .collect();
+
+        if let Some(result) = generate::generate(self.command.clone(), args.clone()) {
+            if let Err(e) = result {
+                eprintln!("{e}");
+            }
+            return ReplInput::Empty;
+        }
+
+        match (self.parse)(world, args) {
+            Ok(()) => ReplInput::Command,
+            Err(e) => {
+                // Help and version are reported as errors too
+                let _ = e.print();
+                ReplInput::Empty
+            }
+        }
+    }
+
+    /// Read lines on background thread. Next line is only read after previous one is
+    /// acknowledged, so that output of the command is printed before the prompt.
+    pub(crate) fn spawn_reader(&self) -> ReplReader {
+        let (line_tx, line_rx) = mpsc::unbounded_channel();
+        let (ack_tx, ack_rx) = std_mpsc::channel();
+        let helper = ReplHelper {
+            command: self.command.clone(),
+        };
+        let prompt = self.prompt.clone();
+        let history = self.history.clone();
+
+        std::thread::Builder::new()
+            .name("dip-repl".to_string())
+            .spawn(move || {
+                let mut editor = match Editor::<ReplHelper>::new() {
+                    Ok(editor) => editor,
+                    Err(e) => {
+                        eprintln!("could not start REPL: {e}");
+                        return;
+                    }
+                };
+                editor.set_helper(Some(helper));
+                if let Some(path) = &history {
+                    // File does not exist on first run
+                    let _ = editor.load_history(path);
+                    if let Some(dir) = path.parent() {
+                        let _ = std::fs::create_dir_all(dir);
+                    }
+                }
+
+                loop {
+                    let line = match editor.readline(&prompt) {
+                        Ok(line) => line,
+                        Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+                        Err(e) => {
+                            eprintln!("{e}");
+                            break;
+                        }
+                    };
+                    if !line.trim().is_empty() {
+                        editor.add_history_entry(line.as_str());
+                        if let Some(path) = &history {
+                            if let Err(e) = editor.save_history(path) {
+                                eprintln!("could not save REPL history: {e}");
+                            }
+                        }
+                    }
+
+                    if line_tx.send(line).is_err() || ack_rx.recv().is_err() {
+                        break;
+                    }
+                }
+            })
+            .unwrap();
+
+        ReplReader { line_rx, ack_tx }
+    }
+}
+
+pub(crate) enum ReplInput {
+    Command,
+    Empty,
+    Exit,
+}
+
+pub(crate) struct ReplReader {
+    pub line_rx: mpsc::UnboundedReceiver<String>,
+    pub ack_tx: std_mpsc::Sender<()>,
+}
+
+// Tab completion for subcommands and long flags derived from clap definition
+struct ReplHelper {
+    command: Command<'static>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let (done, current) = line.split_at(start);
+
+        let mut command = &self.command;
+        for word in done.split_whitespace() {
+            if let Some(subcommand) = command.find_subcommand(word) {
+                command = subcommand;
+            }
+        }
+
+        let candidates = if current.starts_with('-') {
+            command
+                .get_arguments()
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{long}"))
+                .filter(|flag| flag.starts_with(current))
+                .collect::<Vec<_>>()
+        } else {
+            command
+                .get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .map(|subcommand| subcommand.get_name().to_string())
+                .filter(|name| name.starts_with(current))
+                .collect::<Vec<_>>()
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: format!("{candidate} "),
+            })
+            .collect();
+
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,183 @@
+//! Runners for CliPlugin
+
+use crate::{
+    exit::AppExitCode,
+    repl::{Repl, ReplInput, ReplReader},
+};
+use bevy::{
+    app::{App, AppExit},
+    ecs::event::{Events, ManualEventReader},
+    utils::Duration,
+};
+use dip_core::{
+    runtime::async_runtime,
+    task::{AsyncActionFailed, AsyncActionPool},
+};
+use std::future::pending;
+use tokio::{
+    select,
+    sync::mpsc,
+    time::{sleep_until, Instant},
+};
+
+/// Update app once and exit
+pub fn run_oneshot(mut app: App) {
+    app.update();
+
+    exit_with_code(&app);
+}
+
+enum RunnerEvent<AsyncAction> {
+    AsyncAction(AsyncAction),
+    AsyncActionFailed(AsyncActionFailed),
+    Tick,
+    Line(String),
+    Closed,
+}
+
+/// Keep updating app until [`AppExit`] is sent. Exits process with [`AppExitCode`] if it is
+/// not zero.
+///
+/// Parks the thread until an AsyncAction arrives or next tick is reached instead of polling. App
+/// is only updated by AsyncActions when `tick_rate` is `None`.
+pub fn run_continuous<AsyncAction>(app: App, tick_rate: Option<Duration>)
+where
+    AsyncAction: 'static + Send + Sync + Clone,
+{
+    run_loop::<AsyncAction>(app, tick_rate, None);
+}
+
+/// Read commands from stdin and dispatch each of them through subcommand handlers until `exit`
+/// is typed, stdin is closed or [`AppExit`] is sent. AsyncActions and ticks keep updating app in
+/// between commands like [`run_continuous`].
+///
+/// App is first updated once the first command is parsed, so that CLI resources exist for every
+/// system.
+pub fn run_repl<AsyncAction>(app: App, tick_rate: Option<Duration>, repl: Repl)
+where
+    AsyncAction: 'static + Send + Sync + Clone,
+{
+    run_loop::<AsyncAction>(app, tick_rate, Some(repl));
+}
+
+fn run_loop<AsyncAction>(mut app: App, tick_rate: Option<Duration>, mut repl: Option<Repl>)
+where
+    AsyncAction: 'static + Send + Sync + Clone,
+{
+    let (async_action_tx, mut async_action_rx) = mpsc::channel::<AsyncAction>(8);
+    let (async_action_failed_tx, mut async_action_failed_rx) =
+        mpsc::unbounded_channel::<AsyncActionFailed>();
+    let runtime = async_runtime(&mut app.world);
+    let async_action =
+        AsyncActionPool::new(runtime.clone(), async_action_tx, async_action_failed_tx);
+    app.world.insert_resource(async_action);
+
+    let mut app_exit_event_reader = ManualEventReader::<AppExit>::default();
+    let mut next_tick = tick_rate.map(|tick_rate| Instant::now() + tick_rate);
+    let mut reader = repl.as_ref().map(Repl::spawn_reader);
+    let mut started = repl.is_none();
+
+    if started {
+        app.update();
+    }
+
+    loop {
+        if let Some(app_exit_events) = app.world.get_resource::<Events<AppExit>>() {
+            if app_exit_event_reader.iter(app_exit_events).last().is_some() {
+                break;
+            }
+        }
+
+        let event = runtime.block_on(async {
+            select! {
+                Some(action) = async_action_rx.recv() => RunnerEvent::AsyncAction(action),
+                Some(failed) = async_action_failed_rx.recv() => {
+                    RunnerEvent::AsyncActionFailed(failed)
+                }
+                () = tick(next_tick) => RunnerEvent::Tick,
+                event = next_line(&mut reader) => event,
+                else => RunnerEvent::Closed,
+            }
+        });
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("error: {e}");
+                app.world.insert_resource(AppExitCode::FAILURE);
+                break;
+            }
+        };
+
+        match event {
+            RunnerEvent::AsyncAction(action) => {
+                let mut events = app
+                    .world
+                    .get_resource_mut::<Events<AsyncAction>>()
+                    .expect("Provide AsyncAction event to bevy");
+                events.send(action);
+                while let Ok(action) = async_action_rx.try_recv() {
+                    events.send(action);
+                }
+            }
+            RunnerEvent::AsyncActionFailed(failed) => {
+                let mut events = app.world.resource_mut::<Events<AsyncActionFailed>>();
+                events.send(failed);
+                while let Ok(failed) = async_action_failed_rx.try_recv() {
+                    events.send(failed);
+                }
+            }
+            RunnerEvent::Tick => {
+                next_tick = tick_rate.map(|tick_rate| Instant::now() + tick_rate);
+            }
+            RunnerEvent::Line(line) => {
+                let input = repl.as_mut().unwrap().dispatch(&mut app.world, &line);
+                match input {
+                    ReplInput::Command => {
+                        started = true;
+                        app.update();
+                    }
+                    ReplInput::Empty => {}
+                    ReplInput::Exit => break,
+                }
+                if let Some(reader) = &reader {
+                    let _ = reader.ack_tx.send(());
+                }
+                continue;
+            }
+            RunnerEvent::Closed => break,
+        }
+
+        if started {
+            app.update();
+        }
+    }
+
+    exit_with_code(&app);
+}
+
+// Exit process when non-zero AppExitCode is set
+fn exit_with_code(app: &App) {
+    if let Some(AppExitCode(code)) = app.world.get_resource::<AppExitCode>() {
+        if *code != 0 {
+            std::process::exit(*code);
+        }
+    }
+}
+
+// REPL is closed once the reader thread stops sending lines (EOF or Ctrl-C)
+async fn next_line<AsyncAction>(reader: &mut Option<ReplReader>) -> RunnerEvent<AsyncAction> {
+    match reader {
+        Some(reader) => match reader.line_rx.recv().await {
+            Some(line) => RunnerEvent::Line(line),
+            None => RunnerEvent::Closed,
+        },
+        None => pending().await,
+    }
+}
+
+async fn tick(next_tick: Option<Instant>) {
+    match next_tick {
+        Some(next_tick) => sleep_until(next_tick).await,
+        None => pending().await,
+    }
+}
@@ -0,0 +1,167 @@
+//! Tools installed by `dip tool` subcommands
+//!
+//! Implement [`ToolSpec`] and register it to [`ToolRegistry`] to add a tool.
+//!
+//! Built-in tools do not pin digests. Their assets are only verified against checksums
+//! published with each release, i.e. [`ToolSpec::checksum_url`]. Custom tools can pin digests
+//! with [`ToolSpec::checksum`].
+//!
+//! ```
+//! use dip_cli::tool::{Target, ToolRegistry, ToolSpec};
+//!
+//! struct Wasmer;
+//!
+//! impl ToolSpec for Wasmer {
+//!     fn name(&self) -> &'static str {
+//!         "wasmer"
+//!     }
+//!
+//!     fn versions(&self) -> Vec<&'static str> {
+//!         vec!["v3.0.0"]
+//!     }
+//!
+//!     fn asset_name(&self, _version: &str, target: &Target) -> Option<String> {
+//!         Some(format!("wasmer-{}-{}.tar.gz", target.platform.as_str(), target.arch.as_str()))
+//!     }
+//!
+//!     fn download_url(&self, version: &str, target: &Target) -> Option<String> {
+//!         Some(format!(
+//!             "https://github.com/wasmerio/wasmer/releases/download/{version}/{}",
+//!             self.asset_name(version, target)?
+//!         ))
+//!     }
+//! }
+//!
+//! let mut registry = ToolRegistry::default();
+//! registry.register(Wasmer);
+//! assert!(registry.get("wasmer").is_some());
+//! ```
+
+mod builtin;
+mod checksum;
+mod platform;
+
+pub use builtin::{Binaryen, Esbuild, Tailwind, WasmBindgen};
+pub use checksum::{AssetDigest, AssetHasher, Checksum, PinnedChecksum};
+pub use platform::{Arch, Platform, Target};
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+/// How downloaded asset is packed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveType {
+    /// Asset is the executable itself
+    Binary,
+    /// Gzipped tarball containing [`ToolSpec::archive_member`]
+    TarGz,
+}
+
+/// Describes where to download a tool and how to install it. Implement it and register to
+/// [`ToolRegistry`] to add a tool.
+pub trait ToolSpec: Send + Sync + 'static {
+    /// Name used in `dip tool add <name>`
+    fn name(&self) -> &'static str;
+
+    /// Known versions, latest first
+    fn versions(&self) -> Vec<&'static str>;
+
+    /// Version installed when it is not specified. `None` if no version is known.
+    fn default_version(&self) -> Option<&'static str> {
+        self.versions().first().copied()
+    }
+
+    /// Convert version given by user into the form used in release, e.g. `3.2.0` to `v3.2.0`
+    fn normalize_version(&self, version: &str) -> String {
+        version.to_string()
+    }
+
+    /// File name of the release asset. `None` if target is not supported.
+    fn asset_name(&self, version: &str, target: &Target) -> Option<String>;
+
+    /// URL of the release asset. `None` if target is not supported.
+    fn download_url(&self, version: &str, target: &Target) -> Option<String>;
+
+    /// Digest pinned for the version and target, usually looked up with
+    /// [`PinnedChecksum::find`]
+    fn checksum(&self, version: &str, target: &Target) -> Option<Checksum> {
+        let _ = (version, target);
+        None
+    }
+
+    /// Published checksum file. Only fetched when digest is not pinned.
+    fn checksum_url(&self, version: &str, target: &Target) -> Option<String> {
+        let _ = (version, target);
+        None
+    }
+
+    /// Find digest of the asset in content of [`checksum_url`](Self::checksum_url). Defaults to
+    /// `sha256sum` output.
+    fn parse_checksum(&self, content: &str, asset_name: &str) -> Option<Checksum> {
+        Checksum::from_sha256sums(content, asset_name)
+    }
+
+    /// How the asset is packed. Defaults to [`ArchiveType::Binary`].
+    fn archive_type(&self) -> ArchiveType {
+        ArchiveType::Binary
+    }
+
+    /// Path of the executable inside archive
+    fn archive_member(&self, version: &str, target: &Target) -> String {
+        let _ = version;
+        self.bin_name(target)
+    }
+
+    /// File name of the installed executable
+    fn bin_name(&self, target: &Target) -> String {
+        format!("{}{}", self.name(), target.platform.exe_suffix())
+    }
+
+    /// Called after executable is placed
+    fn post_install(&self, bin_path: &Path) -> anyhow::Result<()> {
+        let _ = bin_path;
+        Ok(())
+    }
+}
+
+/// Tools available to `dip tool` subcommands. Default registry contains built-in tools.
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: BTreeMap<&'static str, Arc<dyn ToolSpec>>,
+}
+
+impl ToolRegistry {
+    /// Registry without any tool
+    pub fn empty() -> Self {
+        Self {
+            tools: BTreeMap::new(),
+        }
+    }
+
+    /// Add a tool. Replaces a tool registered with the same name.
+    pub fn register(&mut self, spec: impl ToolSpec) -> &mut Self {
+        self.tools.insert(spec.name(), Arc::new(spec));
+        self
+    }
+
+    /// Find tool by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ToolSpec>> {
+        self.tools.get(name).cloned()
+    }
+
+    /// Tool names in alphabetical order
+    pub fn list(&self) -> Vec<&'static str> {
+        self.tools.keys().copied().collect()
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register(Tailwind)
+            .register(Esbuild)
+            .register(WasmBindgen)
+            .register(Binaryen);
+        registry
+    }
+}
@@ -0,0 +1,227 @@
+use crate::tool::{Arch, ArchiveType, Checksum, Platform, Target, ToolSpec};
+
+/// [Tailwind CSS](https://tailwindcss.com) standalone CLI
+pub struct Tailwind;
+
+impl ToolSpec for Tailwind {
+    fn name(&self) -> &'static str {
+        "tailwindcss"
+    }
+
+    fn versions(&self) -> Vec<&'static str> {
+        vec!["v3.1.8"]
+    }
+
+    fn normalize_version(&self, version: &str) -> String {
+        format!("v{}", version.trim_start_matches('v'))
+    }
+
+    fn asset_name(&self, _version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "tailwindcss-{platform}-{arch}{ext}",
+            platform = target.platform.as_str(),
+            arch = target.arch.as_str(),
+            ext = target.platform.exe_suffix(),
+        ))
+    }
+
+    fn download_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "https://github.com/tailwindlabs/tailwindcss/releases/download/{version}/{asset}",
+            asset = self.asset_name(version, target)?,
+        ))
+    }
+
+    fn checksum_url(&self, version: &str, _target: &Target) -> Option<String> {
+        Some(format!(
+            "https://github.com/tailwindlabs/tailwindcss/releases/download/{version}/sha256sums.txt"
+        ))
+    }
+}
+
+/// [esbuild](https://esbuild.github.io) binary published to npm
+pub struct Esbuild;
+
+impl Esbuild {
+    // npm package with prebuilt binary, e.g. `esbuild-linux-64`
+    fn package(target: &Target) -> String {
+        let platform = match target.platform {
+            Platform::Linux => "linux",
+            Platform::Macos => "darwin",
+            Platform::Windows => "windows",
+        };
+        let arch = match target.arch {
+            Arch::X64 => "64",
+            Arch::Arm64 => "arm64",
+        };
+        format!("esbuild-{platform}-{arch}")
+    }
+}
+
+impl ToolSpec for Esbuild {
+    fn name(&self) -> &'static str {
+        "esbuild"
+    }
+
+    fn versions(&self) -> Vec<&'static str> {
+        vec!["0.15.10"]
+    }
+
+    fn normalize_version(&self, version: &str) -> String {
+        version.trim_start_matches('v').to_string()
+    }
+
+    fn asset_name(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!("{}-{version}.tgz", Self::package(target)))
+    }
+
+    fn download_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "https://registry.npmjs.org/{package}/-/{asset}",
+            package = Self::package(target),
+            asset = self.asset_name(version, target)?,
+        ))
+    }
+
+    fn archive_type(&self) -> ArchiveType {
+        ArchiveType::TarGz
+    }
+
+    // Package metadata of the version, which has `dist.integrity`
+    fn checksum_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "https://registry.npmjs.org/{package}/{version}",
+            package = Self::package(target),
+        ))
+    }
+
+    fn parse_checksum(&self, content: &str, _asset_name: &str) -> Option<Checksum> {
+        let metadata: serde_json::Value = serde_json::from_str(content).ok()?;
+        Checksum::from_integrity(metadata["dist"]["integrity"].as_str()?)
+    }
+
+    fn archive_member(&self, _version: &str, target: &Target) -> String {
+        match target.platform {
+            Platform::Windows => "package/esbuild.exe".to_string(),
+            _ => "package/bin/esbuild".to_string(),
+        }
+    }
+}
+
+/// [wasm-bindgen](https://github.com/rustwasm/wasm-bindgen) CLI
+pub struct WasmBindgen;
+
+impl WasmBindgen {
+    fn triple(target: &Target) -> Option<&'static str> {
+        match (target.platform, target.arch) {
+            (Platform::Linux, Arch::X64) => Some("x86_64-unknown-linux-musl"),
+            (Platform::Linux, Arch::Arm64) => Some("aarch64-unknown-linux-gnu"),
+            (Platform::Macos, Arch::X64) => Some("x86_64-apple-darwin"),
+            (Platform::Windows, Arch::X64) => Some("x86_64-pc-windows-msvc"),
+            _ => None,
+        }
+    }
+}
+
+impl ToolSpec for WasmBindgen {
+    fn name(&self) -> &'static str {
+        "wasm-bindgen"
+    }
+
+    fn versions(&self) -> Vec<&'static str> {
+        vec!["0.2.83"]
+    }
+
+    fn normalize_version(&self, version: &str) -> String {
+        version.trim_start_matches('v').to_string()
+    }
+
+    fn asset_name(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "wasm-bindgen-{version}-{triple}.tar.gz",
+            triple = Self::triple(target)?,
+        ))
+    }
+
+    fn download_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "https://github.com/rustwasm/wasm-bindgen/releases/download/{version}/{asset}",
+            asset = self.asset_name(version, target)?,
+        ))
+    }
+
+    fn archive_type(&self) -> ArchiveType {
+        ArchiveType::TarGz
+    }
+
+    fn checksum_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!("{}.sha256sum", self.download_url(version, target)?))
+    }
+
+    fn archive_member(&self, version: &str, target: &Target) -> String {
+        format!(
+            "wasm-bindgen-{version}-{triple}/{bin}",
+            triple = Self::triple(target).unwrap_or_default(),
+            bin = self.bin_name(target),
+        )
+    }
+}
+
+/// `wasm-opt` from [Binaryen](https://github.com/WebAssembly/binaryen)
+pub struct Binaryen;
+
+impl Binaryen {
+    fn target_name(target: &Target) -> Option<&'static str> {
+        match (target.platform, target.arch) {
+            (Platform::Linux, Arch::X64) => Some("x86_64-linux"),
+            (Platform::Macos, Arch::X64) => Some("x86_64-macos"),
+            (Platform::Macos, Arch::Arm64) => Some("arm64-macos"),
+            (Platform::Windows, Arch::X64) => Some("x86_64-windows"),
+            _ => None,
+        }
+    }
+}
+
+impl ToolSpec for Binaryen {
+    fn name(&self) -> &'static str {
+        "binaryen"
+    }
+
+    fn versions(&self) -> Vec<&'static str> {
+        vec!["version_110"]
+    }
+
+    fn normalize_version(&self, version: &str) -> String {
+        format!("version_{}", version.trim_start_matches("version_"))
+    }
+
+    fn asset_name(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "binaryen-{version}-{target}.tar.gz",
+            target = Self::target_name(target)?,
+        ))
+    }
+
+    fn download_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!(
+            "https://github.com/WebAssembly/binaryen/releases/download/{version}/{asset}",
+            asset = self.asset_name(version, target)?,
+        ))
+    }
+
+    fn archive_type(&self) -> ArchiveType {
+        ArchiveType::TarGz
+    }
+
+    fn checksum_url(&self, version: &str, target: &Target) -> Option<String> {
+        Some(format!("{}.sha256", self.download_url(version, target)?))
+    }
+
+    fn archive_member(&self, version: &str, target: &Target) -> String {
+        format!("binaryen-{version}/bin/{bin}", bin = self.bin_name(target))
+    }
+
+    fn bin_name(&self, target: &Target) -> String {
+        format!("wasm-opt{}", target.platform.exe_suffix())
+    }
+}
@@ -0,0 +1,233 @@
+use crate::tool::{Arch, Platform, Target};
+use anyhow::anyhow;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Expected digest of a release asset in lowercase hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    /// SHA-256 digest
+    Sha256(String),
+    /// SHA-512 digest, e.g. from npm `dist.integrity`
+    Sha512(String),
+}
+
+impl Checksum {
+    /// SHA-256 digest given in hex
+    pub fn sha256(hex: &str) -> Self {
+        Checksum::Sha256(hex.trim().to_lowercase())
+    }
+
+    /// Find digest of the asset in `sha256sum` output. A file with a single digest and no file
+    /// name is accepted too.
+    pub fn from_sha256sums(content: &str, asset_name: &str) -> Option<Self> {
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let entries = lines.clone().count();
+
+        lines.find_map(|line| {
+            let mut words = line.split_whitespace();
+            let digest = words.next()?;
+            match words.next() {
+                Some(name)
+                    if name.trim_start_matches('*').trim_start_matches("./") == asset_name =>
+                {
+                    Some(Self::sha256(digest))
+                }
+                None if entries == 1 => Some(Self::sha256(digest)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Parse Subresource Integrity string such as `sha512-<base64>`
+    pub fn from_integrity(integrity: &str) -> Option<Self> {
+        let (algorithm, digest) = integrity.trim().split_once('-')?;
+        let hex = base64::decode(digest)
+            .ok()?
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+
+        match algorithm {
+            "sha256" => Some(Checksum::Sha256(hex)),
+            "sha512" => Some(Checksum::Sha512(hex)),
+            _ => None,
+        }
+    }
+
+    /// Compare with digest of the downloaded asset
+    pub fn verify(&self, asset_name: &str, digest: &AssetDigest) -> anyhow::Result<()> {
+        let (expected, actual) = match self {
+            Checksum::Sha256(expected) => (expected, &digest.sha256),
+            Checksum::Sha512(expected) => (expected, &digest.sha512),
+        };
+
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Checksum mismatch for {asset_name}: expected {expected}, got {actual}"
+            ))
+        }
+    }
+}
+
+/// Digest pinned for a version and target of a tool
+#[derive(Debug, Clone, Copy)]
+pub struct PinnedChecksum {
+    /// Release version
+    pub version: &'static str,
+    /// Operating system of the asset
+    pub platform: Platform,
+    /// CPU architecture of the asset
+    pub arch: Arch,
+    /// SHA-256 digest in hex
+    pub sha256: &'static str,
+}
+
+impl PinnedChecksum {
+    /// Find digest for the version and target in the table
+    pub fn find(table: &[PinnedChecksum], version: &str, target: &Target) -> Option<Checksum> {
+        table
+            .iter()
+            .find(|pinned| {
+                pinned.version == version
+                    && pinned.platform == target.platform
+                    && pinned.arch == target.arch
+            })
+            .map(|pinned| Checksum::sha256(pinned.sha256))
+    }
+}
+
+/// Digests of downloaded content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetDigest {
+    /// SHA-256 digest in hex
+    pub sha256: String,
+    /// SHA-512 digest in hex
+    pub sha512: String,
+}
+
+/// Computes [`AssetDigest`] while an asset is downloaded
+#[derive(Clone, Default)]
+pub struct AssetHasher {
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+impl AssetHasher {
+    /// Feed downloaded bytes
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.sha256.update(data.as_ref());
+        self.sha512.update(data.as_ref());
+    }
+
+    /// Digests of all bytes fed so far
+    pub fn finalize(self) -> AssetDigest {
+        AssetDigest {
+            sha256: format!("{:x}", self.sha256.finalize()),
+            sha512: format!("{:x}", self.sha512.finalize()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `echo -n hello | sha256sum`
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn hello_digest() -> AssetDigest {
+        let mut hasher = AssetHasher::default();
+        hasher.update(b"hel");
+        hasher.update(b"lo");
+        hasher.finalize()
+    }
+
+    #[test]
+    fn sha256sums_finds_asset_line() {
+        let content = format!(
+            "0000  tailwindcss-linux-arm64\n{}  tailwindcss-linux-x64\n",
+            HELLO_SHA256.to_uppercase()
+        );
+
+        assert_eq!(
+            Checksum::from_sha256sums(&content, "tailwindcss-linux-x64"),
+            Some(Checksum::Sha256(HELLO_SHA256.to_string()))
+        );
+    }
+
+    #[test]
+    fn sha256sums_accepts_binary_marker_and_relative_path() {
+        let content = format!("1111 *./other\n{HELLO_SHA256} *./asset.tar.gz\n");
+
+        assert_eq!(
+            Checksum::from_sha256sums(&content, "asset.tar.gz"),
+            Some(Checksum::Sha256(HELLO_SHA256.to_string()))
+        );
+    }
+
+    #[test]
+    fn sha256sums_accepts_single_digest() {
+        assert_eq!(
+            Checksum::from_sha256sums(&format!("{HELLO_SHA256}\n"), "asset.tar.gz"),
+            Some(Checksum::Sha256(HELLO_SHA256.to_string()))
+        );
+    }
+
+    #[test]
+    fn sha256sums_without_asset_is_none() {
+        let content = format!("{HELLO_SHA256}  other\n0000  another\n");
+
+        assert_eq!(Checksum::from_sha256sums(&content, "asset.tar.gz"), None);
+    }
+
+    #[test]
+    fn integrity_is_decoded_to_hex() {
+        let integrity = format!("sha256-{}", base64::encode([0xab, 0x01]));
+
+        assert_eq!(
+            Checksum::from_integrity(&integrity),
+            Some(Checksum::Sha256("ab01".to_string()))
+        );
+        assert_eq!(Checksum::from_integrity("md5-AAAA"), None);
+    }
+
+    #[test]
+    fn verify_accepts_matching_digest() {
+        assert!(Checksum::sha256(HELLO_SHA256)
+            .verify("asset", &hello_digest())
+            .is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_mismatch() {
+        let error = Checksum::sha256(&"0".repeat(64))
+            .verify("asset", &hello_digest())
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Checksum mismatch for asset"));
+    }
+
+    #[test]
+    fn pinned_checksum_matches_version_and_target() {
+        let table = [PinnedChecksum {
+            version: "v1.0.0",
+            platform: Platform::Linux,
+            arch: Arch::X64,
+            sha256: HELLO_SHA256,
+        }];
+        let linux = Target {
+            platform: Platform::Linux,
+            arch: Arch::X64,
+        };
+        let macos = Target {
+            platform: Platform::Macos,
+            arch: Arch::X64,
+        };
+
+        assert!(PinnedChecksum::find(&table, "v1.0.0", &linux).is_some());
+        assert!(PinnedChecksum::find(&table, "v1.0.0", &macos).is_none());
+        assert!(PinnedChecksum::find(&table, "v2.0.0", &linux).is_none());
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::anyhow;
+
+/// Operating system of release assets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Linux
+    Linux,
+    /// macOS
+    Macos,
+    /// Windows
+    Windows,
+}
+
+impl Platform {
+    /// Platform this binary is built for
+    pub fn current() -> anyhow::Result<Self> {
+        match std::env::consts::OS {
+            "linux" => Ok(Platform::Linux),
+            "macos" => Ok(Platform::Macos),
+            "windows" => Ok(Platform::Windows),
+            os => Err(anyhow!("unsupported platform: {os}")),
+        }
+    }
+
+    /// Name used in release asset names
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Linux => "linux",
+            Platform::Macos => "macos",
+            Platform::Windows => "windows",
+        }
+    }
+
+    /// Extension of executable files
+    pub fn exe_suffix(&self) -> &'static str {
+        match self {
+            Platform::Windows => ".exe",
+            _ => "",
+        }
+    }
+}
+
+/// CPU architecture of release assets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    /// x86 and x86_64
+    X64,
+    /// ARM and AArch64
+    Arm64,
+}
+
+impl Arch {
+    /// Architecture this binary is built for
+    pub fn current() -> anyhow::Result<Self> {
+        match std::env::consts::ARCH {
+            "x86" | "x86_64" => Ok(Arch::X64),
+            "arm" | "aarch64" => Ok(Arch::Arm64),
+            arch => Err(anyhow!("unsupported arch: {arch}")),
+        }
+    }
+
+    /// Name used in release asset names
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Arch::X64 => "x64",
+            Arch::Arm64 => "arm64",
+        }
+    }
+}
+
+/// Platform and architecture to download tool binaries for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    /// Operating system
+    pub platform: Platform,
+    /// CPU architecture
+    pub arch: Arch,
+}
+
+impl Target {
+    /// Target this binary is built for
+    pub fn current() -> anyhow::Result<Self> {
+        Ok(Self {
+            platform: Platform::current()?,
+            arch: Arch::current()?,
+        })
+    }
+}
@@ -0,0 +1,683 @@
+//! Runtime support for `#[derive(ConfigPlugin)]`
+
+// `ConfigError` is sent as event and stored as resource as is
+#![allow(clippy::result_large_err)]
+
+use config::{
+    builder::{ConfigBuilder, DefaultState},
+    Map, Source,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    env,
+    ffi::OsString,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex,
+    },
+};
+
+mod schema;
+
+pub use dip_macro::ConfigPlugin;
+pub use schema::{documented_default, effective_entries, schema, ConfigEntry};
+
+/// Event emitted when config resource is updated from changed files
+#[derive(Debug, Clone)]
+pub struct ConfigReloaded {
+    /// Files that triggered the reload
+    pub paths: Vec<PathBuf>,
+}
+
+/// Emitted as event and stored as resource when config could not be loaded or deserialized
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    /// What went wrong
+    pub kind: ConfigErrorKind,
+    /// File where the error is found
+    pub file: Option<String>,
+    /// Key path of the invalid value, e.g. `backend.api_key`
+    pub key: Option<String>,
+    /// Expected type of the value
+    pub expected: Option<String>,
+    /// Actual value found in the file
+    pub found: Option<String>,
+    /// Error message from config builder or deserializer
+    pub message: String,
+}
+
+/// Category of [`ConfigError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigErrorKind {
+    /// File is malformed
+    Parse,
+    /// Value has unexpected type
+    Type,
+    /// Required key is missing
+    Missing,
+    /// Any other error, e.g. required file doesn't exist
+    Other,
+}
+
+impl ConfigError {
+    /// Multi-line report to print in CLI apps
+    ///
+    /// ```text
+    /// error: invalid type: string "8080", expected u16 for key `server.port`
+    ///   --> /home/user/.config/app.toml
+    ///    = key: server.port
+    ///    = expected: u16, found: string "8080"
+    /// ```
+    pub fn report(&self) -> String {
+        let mut report = format!("error: {}", self.message);
+        if let Some(file) = &self.file {
+            report.push_str(&format!("\n  --> {file}"));
+        }
+        if let Some(key) = &self.key {
+            report.push_str(&format!("\n   = key: {key}"));
+        }
+        match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => {
+                report.push_str(&format!("\n   = expected: {expected}, found: {found}"))
+            }
+            (Some(expected), None) => report.push_str(&format!("\n   = expected: {expected}")),
+            _ => {}
+        }
+
+        report
+    }
+}
+
+impl ConfigError {
+    pub(crate) fn message(e: impl Display) -> Self {
+        Self {
+            kind: ConfigErrorKind::Other,
+            file: None,
+            key: None,
+            expected: None,
+            found: None,
+            message: e.to_string(),
+        }
+    }
+
+    fn other(file: &Path, e: impl Display) -> Self {
+        Self {
+            file: Some(file.display().to_string()),
+            ..Self::message(e)
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<config::ConfigError> for ConfigError {
+    fn from(e: config::ConfigError) -> Self {
+        let message = e.to_string();
+        let mut error = Self {
+            kind: ConfigErrorKind::Other,
+            file: None,
+            key: None,
+            expected: None,
+            found: None,
+            message,
+        };
+
+        match e {
+            config::ConfigError::FileParse { uri, .. } => {
+                error.kind = ConfigErrorKind::Parse;
+                error.file = uri;
+            }
+            config::ConfigError::Type {
+                origin,
+                unexpected,
+                expected,
+                key,
+            } => {
+                error.kind = ConfigErrorKind::Type;
+                error.file = origin;
+                error.key = key;
+                error.expected = Some(expected.to_string());
+                error.found = Some(unexpected.to_string());
+            }
+            config::ConfigError::NotFound(key) => {
+                error.kind = ConfigErrorKind::Missing;
+                error.key = Some(key);
+            }
+            _ => {}
+        }
+
+        error
+    }
+}
+
+/// Config files read by ConfigPlugin. Inserted as resource to save edited config.
+///
+/// ```no_run
+/// # use bevy::ecs::system::Res;
+/// # use config::builder::{ConfigBuilder, DefaultState};
+/// # use dip_core::config::{ConfigFiles, ConfigOverrides};
+/// # #[derive(serde::Serialize, serde::Deserialize)]
+/// # struct Config { theme: String }
+/// fn save_config(
+///     config: Res<Config>,
+///     files: Res<ConfigFiles>,
+///     builder: Res<ConfigBuilder<DefaultState>>,
+///     overrides: Option<Res<ConfigOverrides>>,
+/// ) {
+///     if let Err(e) = files.save(&*config, &builder, overrides.as_deref()) {
+///         eprintln!("{}", e.report());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigFiles {
+    /// Files without extension in the order of priority, lowest first
+    pub names: Vec<String>,
+    /// User-owned files without extension which config can be saved to, lowest priority first
+    pub writable: Vec<String>,
+    /// File without extension to create when none of the writable files exists
+    pub user_file: String,
+}
+
+impl ConfigFiles {
+    const EXTENSIONS: [&'static str; 7] = ["toml", "yaml", "yml", "json", "json5", "ini", "ron"];
+    const SAVABLE_EXTENSIONS: [&'static str; 4] = ["toml", "yaml", "yml", "json"];
+
+    /// Existing files in the order of priority, lowest first. These are the sources loaded by
+    /// config, useful for debugging which files are in effect.
+    pub fn loaded(&self) -> Vec<PathBuf> {
+        self.names
+            .iter()
+            .filter_map(|name| find_file(name))
+            .collect()
+    }
+
+    /// Highest-priority existing writable file in a format it can save, or `user_file` in
+    /// TOML format. System paths, profile overlays and `CONFIG_PATH` are never written.
+    pub fn writable_path(&self) -> PathBuf {
+        self.writable
+            .iter()
+            .rev()
+            .find_map(|name| {
+                Self::SAVABLE_EXTENSIONS
+                    .iter()
+                    .map(|ext| PathBuf::from(format!("{name}.{ext}")))
+                    .find(|path| path.is_file())
+            })
+            .unwrap_or_else(|| PathBuf::from(format!("{}.toml", self.user_file)))
+    }
+
+    /// Write values of `config` that differ from the config loaded from `builder` and
+    /// `overrides` to [`writable_path`](Self::writable_path) in its format (TOML, YAML or JSON).
+    /// So values from system files, environment and CLI overrides are only written when they
+    /// are changed. Other keys in the file are kept. Returns path of the written file.
+    pub fn save<C: Serialize + DeserializeOwned>(
+        &self,
+        config: &C,
+        builder: &ConfigBuilder<DefaultState>,
+        overrides: Option<&ConfigOverrides>,
+    ) -> Result<PathBuf, ConfigError> {
+        let path = self.writable_path();
+        let builder = match overrides {
+            Some(overrides) => overrides.apply(builder.clone())?,
+            None => builder.clone(),
+        };
+        let loaded = builder.build()?.try_deserialize::<C>()?;
+        let loaded = serde_json::to_value(loaded).map_err(|e| ConfigError::other(&path, e))?;
+        let value = serde_json::to_value(config).map_err(|e| ConfigError::other(&path, e))?;
+        let value = match changes(value, &loaded) {
+            Some(value) => value,
+            None => return Ok(path),
+        };
+
+        let mut merged = if path.exists() {
+            let content = fs::read_to_string(&path).map_err(|e| ConfigError::other(&path, e))?;
+            Self::parse(&path, &content)?
+        } else {
+            Value::Object(Default::default())
+        };
+        merge(&mut merged, value);
+
+        let content = Self::serialize(&path, &merged)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| ConfigError::other(&path, e))?;
+        }
+        fs::write(&path, content).map_err(|e| ConfigError::other(&path, e))?;
+
+        Ok(path)
+    }
+
+    fn extension(path: &Path) -> Option<&str> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| Self::EXTENSIONS.contains(ext))
+    }
+
+    fn parse(path: &Path, content: &str) -> Result<Value, ConfigError> {
+        let value = match Self::extension(path) {
+            Some("toml") => toml::from_str(content).map_err(|e| e.to_string()),
+            Some("yaml" | "yml") => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+            _ => Err("unsupported config format".to_string()),
+        };
+
+        value.map_err(|message| ConfigError {
+            kind: ConfigErrorKind::Parse,
+            ..ConfigError::other(path, message)
+        })
+    }
+
+    fn serialize(path: &Path, value: &Value) -> Result<String, ConfigError> {
+        let content = match Self::extension(path) {
+            Some("toml") => toml::Value::try_from(value)
+                .and_then(|value| toml::to_string_pretty(&value))
+                .map_err(|e| e.to_string()),
+            Some("yaml" | "yml") => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            Some("json") => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            _ => Err("unsupported config format".to_string()),
+        };
+
+        content.map_err(|message| ConfigError::other(path, message))
+    }
+}
+
+/// Find existing file like [`config::File::with_name`] does, except that extension is appended
+/// instead of replaced. So `app.dev` finds `app.dev.toml`.
+pub fn find_file(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name);
+    if path.is_file() && ConfigFiles::extension(&path).is_some() {
+        return Some(path);
+    }
+
+    ConfigFiles::EXTENSIONS
+        .iter()
+        .map(|ext| PathBuf::from(format!("{name}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+/// Optional config file source which is looked up with [`find_file`] every time config is
+/// built, so that a file created after startup is loaded on reload.
+#[derive(Debug, Clone)]
+pub struct ConfigFileSource {
+    name: String,
+}
+
+impl ConfigFileSource {
+    /// Source for the file given without extension
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl Source for ConfigFileSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, config::Value>, config::ConfigError> {
+        match find_file(&self.name) {
+            Some(path) => config::File::from(path).required(false).collect(),
+            None => Ok(Map::new()),
+        }
+    }
+}
+
+// Part of `value` that differs from `loaded`. `None` when nothing changed.
+fn changes(value: Value, loaded: &Value) -> Option<Value> {
+    match (value, loaded) {
+        (Value::Object(value), Value::Object(loaded)) => {
+            let changed: serde_json::Map<_, _> = value
+                .into_iter()
+                .filter_map(|(key, value)| match loaded.get(&key) {
+                    Some(loaded) => changes(value, loaded).map(|value| (key, value)),
+                    None => Some((key, value)),
+                })
+                .collect();
+            (!changed.is_empty()).then_some(Value::Object(changed))
+        }
+        (value, loaded) => (&value != loaded).then_some(value),
+    }
+}
+
+// Overwrite keys of `base` with `value`. `null` removes the key since TOML can't represent it.
+fn merge(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, value) in value {
+                if value.is_null() {
+                    base.remove(&key);
+                } else {
+                    merge(base.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// Where ConfigPlugin looks for config files
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+    /// Name of config directory and file, e.g. `app` for `~/.config/app/app.toml`
+    pub app_name: String,
+    /// Overlay `{file}.{profile}` is loaded on top of each file when set
+    pub profile: Option<String>,
+    /// Files without extension, lowest priority first. Platform defaults are used when `None`.
+    pub search_paths: Option<Vec<String>>,
+}
+
+impl ConfigPaths {
+    /// Default paths for the app. Profile is read from `{APP_NAME}_PROFILE` env var.
+    pub fn new(app_name: impl Into<String>) -> Self {
+        let app_name = app_name.into();
+        let profile_env = format!("{}_PROFILE", app_name.to_uppercase().replace('-', "_"));
+
+        Self {
+            profile: env::var(profile_env).ok().filter(|p| !p.is_empty()),
+            app_name,
+            search_paths: None,
+        }
+    }
+
+    /// Platform search paths, lowest priority first:
+    ///
+    /// 1. `/etc/{name}/{name}` and `$XDG_CONFIG_DIRS/{name}/{name}` (unix only)
+    /// 2. `$XDG_CONFIG_HOME/{name}` and `$XDG_CONFIG_HOME/{name}/{name}`. Falls back to platform
+    ///    config directory, e.g. `~/.config` on Linux or `~/Library/Application Support` on macOS.
+    /// 3. `$HOME/.{name}`
+    /// 4. `./{name}`
+    pub fn default_search_paths(&self) -> Vec<String> {
+        let name = &self.app_name;
+        let mut paths = vec![];
+
+        for dir in system_config_dirs() {
+            paths.push(format!("{}/{name}/{name}", dir.display()));
+        }
+        paths.extend(self.user_files());
+        paths.push(name.clone());
+
+        paths
+    }
+
+    /// Search paths followed by profile overlay of each path
+    pub fn file_names(&self) -> Vec<String> {
+        let search_paths = self
+            .search_paths
+            .clone()
+            .unwrap_or_else(|| self.default_search_paths());
+
+        let mut names = vec![];
+        for path in search_paths {
+            if let Some(profile) = &self.profile {
+                names.push(path.clone());
+                names.push(format!("{path}.{profile}"));
+            } else {
+                names.push(path);
+            }
+        }
+
+        names
+    }
+
+    /// Files in user-owned directories, lowest priority first: `{config_home}/{name}`,
+    /// `{config_home}/{name}/{name}` and `$HOME/.{name}`
+    pub fn user_files(&self) -> Vec<String> {
+        let name = &self.app_name;
+        let mut paths = vec![];
+
+        if let Some(dir) = config_home() {
+            paths.push(format!("{}/{name}", dir.display()));
+            paths.push(format!("{}/{name}/{name}", dir.display()));
+        }
+        if let Some(dir) = dirs::home_dir() {
+            paths.push(format!("{}/.{name}", dir.display()));
+        }
+
+        paths
+    }
+
+    /// `{config_home}/{name}/{name}`, created when config is saved for the first time
+    pub fn user_file(&self) -> String {
+        let dir = config_home().unwrap_or_else(|| PathBuf::from("."));
+        format!("{}/{name}/{name}", dir.display(), name = self.app_name)
+    }
+}
+
+// $XDG_CONFIG_HOME is respected on every platform when it is set
+fn config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(dirs::config_dir)
+}
+
+// Lowest priority first
+#[cfg(unix)]
+fn system_config_dirs() -> Vec<PathBuf> {
+    let xdg_config_dirs = env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+
+    let mut config_dirs = vec![PathBuf::from("/etc")];
+    // First entry of XDG_CONFIG_DIRS is the most important one
+    config_dirs.extend(
+        xdg_config_dirs
+            .split(':')
+            .rev()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute()),
+    );
+
+    config_dirs
+}
+
+#[cfg(not(unix))]
+fn system_config_dirs() -> Vec<PathBuf> {
+    vec![]
+}
+
+/// Config values with the highest priority, above default string, files and env vars.
+///
+/// CliPlugin inserts it from CLI fields marked with `#[config(key = "server.port")]` when they
+/// are given on command line, and from `Vec<String>` fields of `key=value` pairs marked with
+/// `#[config(overrides)]`. An invalid pair fails parsing.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    values: Vec<(String, String)>,
+}
+
+impl ConfigOverrides {
+    /// Override value of the key path
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.push((key.into(), value.into()));
+    }
+
+    /// Override value from `key=value` pair
+    pub fn set_pair(&mut self, pair: &str) -> Result<(), String> {
+        match pair.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                self.set(key.trim(), value.trim());
+                Ok(())
+            }
+            _ => Err(format!(
+                "invalid config override `{pair}`, expected `key=value`"
+            )),
+        }
+    }
+
+    /// Overridden key and value pairs in the order they are set
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Add overrides to the builder
+    pub fn apply(
+        &self,
+        mut builder: ConfigBuilder<DefaultState>,
+    ) -> Result<ConfigBuilder<DefaultState>, ConfigError> {
+        for (key, value) in self.iter() {
+            builder = builder.set_override(key, value)?;
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Inserted by ConfigPlugin when it should exit process on config error instead of sending
+/// `AppExit`
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigExitOnError;
+
+/// Config used in place of the one failed to load. Inserted as resource by ConfigPlugin.
+pub struct ConfigFallback<C>(pub fn() -> C);
+
+/// Watch config files and report changes. Inserted as resource when hot reload is enabled in
+/// ConfigPlugin.
+///
+/// Files are identified like [`config::File::with_name`], so `~/.config/app/app` matches
+/// `app.toml`, `app.json`, etc. Files don't need to exist. When their directory is missing,
+/// the closest existing ancestor is watched until the directory is created.
+pub struct ConfigWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    changes: Mutex<Receiver<PathBuf>>,
+    // Absolute directory, which may not exist yet, and file name of each config file
+    watched: Vec<(PathBuf, OsString)>,
+    // Directories registered to the watcher
+    dirs: Vec<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Start watching without any file
+    pub fn new() -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        })?;
+
+        Ok(Self {
+            watcher: Mutex::new(watcher),
+            changes: Mutex::new(rx),
+            watched: vec![],
+            dirs: vec![],
+        })
+    }
+
+    /// Watch config file. Path is given without extension like [`config::File::with_name`].
+    pub fn watch(&mut self, name: impl AsRef<Path>) {
+        let name = name.as_ref();
+        let (dir, file_name) = match (name.parent(), name.file_name()) {
+            (Some(dir), Some(file_name)) => (dir, file_name),
+            _ => return,
+        };
+        let dir = match env::current_dir() {
+            Ok(current_dir) => current_dir.join(dir),
+            Err(_) => return,
+        };
+        let dir = dir.canonicalize().unwrap_or(dir);
+
+        self.watch_dir(&dir);
+        self.watched.push((dir, file_name.to_os_string()));
+    }
+
+    /// Drain changes of watched files since last call
+    pub fn changed_files(&mut self) -> Vec<PathBuf> {
+        let events = self
+            .changes
+            .get_mut()
+            .unwrap()
+            .try_iter()
+            .collect::<Vec<_>>();
+
+        let mut paths = vec![];
+        for path in events {
+            let changed = if path.is_dir() {
+                self.watch_created_dir(&path)
+            } else if self.is_watched(&path) {
+                vec![path]
+            } else {
+                vec![]
+            };
+
+            for path in changed {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        paths
+    }
+
+    // Watch `dir`, or its closest existing ancestor so that creation of `dir` is noticed.
+    // Returns whether `dir` itself is watched.
+    fn watch_dir(&mut self, dir: &Path) -> bool {
+        let existing = match dir.ancestors().find(|ancestor| ancestor.is_dir()) {
+            Some(existing) => existing,
+            None => return false,
+        };
+
+        if !self.dirs.iter().any(|watched| watched == existing) {
+            let watcher = self.watcher.get_mut().unwrap();
+            if watcher
+                .watch(existing, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                return false;
+            }
+            self.dirs.push(existing.to_path_buf());
+        }
+
+        existing == dir
+    }
+
+    // Watch directories of config files that are created at or below `created`. Returns
+    // config files already written in them before they were watched.
+    fn watch_created_dir(&mut self, created: &Path) -> Vec<PathBuf> {
+        let pending = self
+            .watched
+            .iter()
+            .filter(|(dir, _)| dir.starts_with(created) && !self.dirs.contains(dir))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut paths = vec![];
+        for (dir, name) in pending {
+            if self.watch_dir(&dir) {
+                paths.extend(find_file(&dir.join(name).to_string_lossy()));
+            }
+        }
+
+        paths
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        let dir = match path.parent() {
+            Some(dir) => dir,
+            None => return false,
+        };
+
+        self.watched.iter().any(|(watched_dir, name)| {
+            watched_dir == dir
+                && (path.file_stem() == Some(name.as_os_str())
+                    || path.file_name() == Some(name.as_os_str()))
+        })
+    }
+}
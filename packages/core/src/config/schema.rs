@@ -0,0 +1,203 @@
+//! JSON Schema, documented default file and effective values of config
+
+use super::{ConfigError, ConfigOverrides};
+use config::{
+    builder::{ConfigBuilder, DefaultState},
+    Source, Value as ConfigValue, ValueKind,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// JSON Schema of the config. Doc comments of fields become descriptions.
+pub fn schema<C: JsonSchema>() -> Value {
+    serde_json::to_value(schemars::schema_for!(C)).unwrap()
+}
+
+/// TOML file filled with values of `config`. Each key is preceded by doc comment of the field.
+///
+/// ```toml
+/// # Base url of the API
+/// base_url = "localhost:1111"
+///
+/// [backend]
+/// # Key issued by the backend
+/// api_key = "default-api-key"
+/// ```
+pub fn documented_default<C: JsonSchema + Serialize>(config: &C) -> Result<String, ConfigError> {
+    let value = serde_json::to_value(config).map_err(ConfigError::message)?;
+    let table = match value {
+        Value::Object(table) => table,
+        _ => return Err(ConfigError::message("config must be a struct")),
+    };
+    let schema = schema::<C>();
+    let defs = schema.get("definitions").cloned().unwrap_or(Value::Null);
+
+    let mut out = String::new();
+    write_table(&mut out, &[], &table, &schema, &defs)?;
+
+    Ok(out.trim_start().to_string())
+}
+
+/// Value of the merged config with the source it came from
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    /// Key path, e.g. `backend.api_key`
+    pub key: String,
+    /// Effective value
+    pub value: String,
+    /// File path, `the environment`, `override` or `default`
+    pub origin: String,
+}
+
+/// Build config like ConfigPlugin does and list every value with its origin, sorted by key
+pub fn effective_entries(
+    builder: &ConfigBuilder<DefaultState>,
+    overrides: Option<&ConfigOverrides>,
+) -> Result<Vec<ConfigEntry>, ConfigError> {
+    let builder = match overrides {
+        Some(overrides) => overrides.apply(builder.clone())?,
+        None => builder.clone(),
+    };
+    let table = builder.build()?.collect()?;
+
+    let mut entries = vec![];
+    for (key, value) in table {
+        collect_entries(&mut entries, key, value, overrides);
+    }
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Ok(entries)
+}
+
+fn collect_entries(
+    entries: &mut Vec<ConfigEntry>,
+    key: String,
+    value: ConfigValue,
+    overrides: Option<&ConfigOverrides>,
+) {
+    if let ValueKind::Table(table) = value.kind {
+        for (child, value) in table {
+            collect_entries(entries, format!("{key}.{child}"), value, overrides);
+        }
+        return;
+    }
+
+    // Sources without uri are the default string and overrides
+    let origin = match origin_of(&value) {
+        Some(origin) => origin,
+        None if overrides.is_some_and(|o| o.iter().any(|(k, _)| k == key)) => {
+            "override".to_string()
+        }
+        None => "default".to_string(),
+    };
+
+    entries.push(ConfigEntry {
+        value: value.to_string(),
+        key,
+        origin,
+    });
+}
+
+// `Value::origin` is not public in released config, but type errors carry the origin
+fn origin_of(value: &ConfigValue) -> Option<String> {
+    match value.clone().into_table() {
+        Err(config::ConfigError::Type { origin, .. }) => origin,
+        _ => None,
+    }
+}
+
+fn write_table(
+    out: &mut String,
+    path: &[&str],
+    table: &Map<String, Value>,
+    schema: &Value,
+    defs: &Value,
+) -> Result<(), ConfigError> {
+    let schema = resolve(schema, defs);
+    let properties = schema.get("properties");
+
+    // TOML requires plain values before sub tables
+    for (key, value) in table.iter().filter(|(_, v)| !v.is_object()) {
+        let field_schema = properties.and_then(|p| p.get(key));
+        write_description(out, field_schema, defs);
+        match value {
+            Value::Null => writeln!(out, "# {} =", toml_key(key)).unwrap(),
+            value => {
+                let value = toml::Value::try_from(value).map_err(ConfigError::message)?;
+                writeln!(out, "{} = {value}", toml_key(key)).unwrap();
+            }
+        }
+    }
+
+    for (key, value) in table.iter() {
+        if let Value::Object(child) = value {
+            let field_schema = properties.and_then(|p| p.get(key));
+            let mut child_path = path.to_vec();
+            child_path.push(key);
+
+            out.push('\n');
+            write_description(out, field_schema, defs);
+            let header = child_path
+                .iter()
+                .map(|k| toml_key(k))
+                .collect::<Vec<_>>()
+                .join(".");
+            writeln!(out, "[{header}]").unwrap();
+            write_table(
+                out,
+                &child_path,
+                child,
+                field_schema.unwrap_or(&Value::Null),
+                defs,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_description(out: &mut String, schema: Option<&Value>, defs: &Value) {
+    let description = schema.and_then(|schema| {
+        schema
+            .get("description")
+            .or_else(|| resolve(schema, defs).get("description"))
+            .and_then(Value::as_str)
+    });
+
+    if let Some(description) = description {
+        for line in description.lines() {
+            writeln!(out, "# {line}").unwrap();
+        }
+    }
+}
+
+// Follow `$ref` to definitions. Documented fields wrap the reference in `allOf`.
+fn resolve<'a>(schema: &'a Value, defs: &'a Value) -> &'a Value {
+    let reference = schema.get("$ref").or_else(|| {
+        schema
+            .get("allOf")
+            .and_then(|all_of| all_of.get(0))
+            .and_then(|s| s.get("$ref"))
+    });
+
+    reference
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/definitions/"))
+        .and_then(|name| defs.get(name))
+        .unwrap_or(schema)
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if bare {
+        key.to_string()
+    } else {
+        format!("{key:?}")
+    }
+}
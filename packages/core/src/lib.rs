@@ -1,15 +1,17 @@
 //! Shared resources across platforms
 
+pub mod config;
+pub mod runtime;
 pub mod schedule;
 pub mod ui_state;
 
-pub mod config {
-    pub use dip_macro::ConfigPlugin;
-}
 pub use dip_task as task;
 
 pub mod prelude {
     pub use crate::{
+        config::{
+            ConfigError, ConfigErrorKind, ConfigFiles, ConfigOverrides, ConfigPaths, ConfigReloaded,
+        },
         schedule::{DipStage, DipStartupStage, UiSchedulePlugin},
         task::prelude::*,
         ui_state::{NoRootProps, NoUiAction, NoUiState, UiStateHandler},
@@ -0,0 +1,21 @@
+//! Shared tokio runtime
+
+use crate::task::{AsyncRuntime, AsyncRuntimeSettings};
+use bevy::ecs::world::World;
+
+/// Get shared [`AsyncRuntime`] resource. On first call it is built from [`AsyncRuntimeSettings`]
+/// resource (or default settings) and inserted to the world.
+pub fn async_runtime(world: &mut World) -> AsyncRuntime {
+    if let Some(runtime) = world.get_resource::<AsyncRuntime>() {
+        return runtime.clone();
+    }
+
+    let settings = world
+        .get_resource::<AsyncRuntimeSettings>()
+        .cloned()
+        .unwrap_or_default();
+    let runtime = AsyncRuntime::new(&settings);
+    world.insert_resource(runtime.clone());
+
+    runtime
+}
@@ -3,11 +3,12 @@ use std::rc::Rc;
 
 /// Trait to provide custom handerl for Ui states. This trait is automatically implemented with UiStatePlugin macro.
 pub trait UiStateHandler {
-    ///
+    /// Apply state changes to atoms
     fn handler(self, _atom_root: Rc<AtomRoot>);
 }
 
 /// Placeholder
+#[derive(Clone)]
 pub struct NoUiState;
 
 impl UiStateHandler for NoUiState {
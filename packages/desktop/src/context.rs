@@ -1,16 +1,58 @@
-use crate::event::{
-    UiEvent::{self, *},
-    WindowEvent::*,
-};
+use crate::event::{UiEvent, WindowEvent, WindowEvent::*};
+use bevy::window::WindowId;
 use std::fmt::Debug;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{self, Sender};
 use wry::application::event_loop::EventLoopProxy;
 
 pub type ProxyType<UiAction, AsyncAction> = EventLoopProxy<UiEvent<UiAction, AsyncAction>>;
 
+/// Destination of [`UiEvent`]s. Either tao event loop or a plain channel when running headless.
+pub enum UiEventProxy<UiAction: Debug + 'static, AsyncAction: 'static> {
+    EventLoop(ProxyType<UiAction, AsyncAction>),
+    Channel(mpsc::UnboundedSender<UiEvent<UiAction, AsyncAction>>),
+}
+
+impl<UiAction, AsyncAction> UiEventProxy<UiAction, AsyncAction>
+where
+    UiAction: Debug,
+{
+    pub fn send_event(
+        &self,
+        event: UiEvent<UiAction, AsyncAction>,
+    ) -> Result<(), UiEvent<UiAction, AsyncAction>> {
+        match self {
+            UiEventProxy::EventLoop(proxy) => proxy.send_event(event).map_err(|e| e.0),
+            UiEventProxy::Channel(tx) => tx.send(event).map_err(|e| e.0),
+        }
+    }
+}
+
+impl<UiAction, AsyncAction> Clone for UiEventProxy<UiAction, AsyncAction>
+where
+    UiAction: Debug,
+{
+    fn clone(&self) -> Self {
+        match self {
+            UiEventProxy::EventLoop(proxy) => UiEventProxy::EventLoop(proxy.clone()),
+            UiEventProxy::Channel(tx) => UiEventProxy::Channel(tx.clone()),
+        }
+    }
+}
+
+impl<UiAction, AsyncAction> From<ProxyType<UiAction, AsyncAction>>
+    for UiEventProxy<UiAction, AsyncAction>
+where
+    UiAction: Debug,
+{
+    fn from(proxy: ProxyType<UiAction, AsyncAction>) -> Self {
+        UiEventProxy::EventLoop(proxy)
+    }
+}
+
 #[derive(Clone)]
 pub struct UiContext<UiAction: Debug + 'static + Clone, AsyncAction: 'static> {
-    proxy: ProxyType<UiAction, AsyncAction>,
+    window_id: WindowId,
+    proxy: UiEventProxy<UiAction, AsyncAction>,
     ui_action_tx: Sender<UiAction>,
 }
 
@@ -19,13 +61,28 @@ where
     UiAction: Debug + Clone,
     AsyncAction: Debug + Clone,
 {
-    pub fn new(proxy: ProxyType<UiAction, AsyncAction>, ui_action_tx: Sender<UiAction>) -> Self {
+    pub fn new(
+        window_id: WindowId,
+        proxy: impl Into<UiEventProxy<UiAction, AsyncAction>>,
+        ui_action_tx: Sender<UiAction>,
+    ) -> Self {
         Self {
-            proxy,
+            window_id,
+            proxy: proxy.into(),
             ui_action_tx,
         }
     }
 
+    /// Id of the window this context belongs to
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    fn send_window_event(&self, event: WindowEvent) -> Result<(), UiEvent<UiAction, AsyncAction>> {
+        self.proxy
+            .send_event(UiEvent::WindowEvent(self.window_id, event))
+    }
+
     pub fn send(&self, action: UiAction) {
         self.ui_action_tx
             .try_send(action)
@@ -33,76 +90,70 @@ where
     }
 
     pub fn drag(&self) {
-        let _ = self.proxy.send_event(WindowEvent(DragWindow));
+        let _ = self.send_window_event(DragWindow);
     }
 
     pub fn set_minimized(&self, minimized: bool) {
-        let _ = self.proxy.send_event(WindowEvent(Minimize(minimized)));
+        let _ = self.send_window_event(Minimize(minimized));
     }
 
     pub fn set_maximized(&self, maximized: bool) {
-        let _ = self.proxy.send_event(WindowEvent(Maximize(maximized)));
+        let _ = self.send_window_event(Maximize(maximized));
     }
 
     pub fn toggle_maximized(&self) {
-        let _ = self.proxy.send_event(WindowEvent(MaximizeToggle));
+        let _ = self.send_window_event(MaximizeToggle);
     }
 
     pub fn set_visible(&self, visible: bool) {
-        let _ = self.proxy.send_event(WindowEvent(Visible(visible)));
+        let _ = self.send_window_event(Visible(visible));
     }
 
     pub fn close(&self) {
-        let _ = self.proxy.send_event(WindowEvent(CloseWindow));
+        let _ = self.send_window_event(CloseWindow);
     }
 
     pub fn focus(&self) {
-        let _ = self.proxy.send_event(WindowEvent(FocusWindow));
+        let _ = self.send_window_event(FocusWindow);
     }
 
     pub fn set_fullscreen(&self, fullscreen: bool) {
-        let _ = self.proxy.send_event(WindowEvent(Fullscreen(fullscreen)));
+        let _ = self.send_window_event(Fullscreen(fullscreen));
     }
 
     pub fn set_resizable(&self, resizable: bool) {
-        let _ = self.proxy.send_event(WindowEvent(Resizable(resizable)));
+        let _ = self.send_window_event(Resizable(resizable));
     }
 
     pub fn set_always_on_top(&self, top: bool) {
-        let _ = self.proxy.send_event(WindowEvent(AlwaysOnTop(top)));
+        let _ = self.send_window_event(AlwaysOnTop(top));
     }
 
     pub fn set_cursor_visible(&self, visible: bool) {
-        let _ = self.proxy.send_event(WindowEvent(CursorVisible(visible)));
+        let _ = self.send_window_event(CursorVisible(visible));
     }
 
     pub fn set_cursor_grab(&self, grab: bool) {
-        let _ = self.proxy.send_event(WindowEvent(CursorGrab(grab)));
+        let _ = self.send_window_event(CursorGrab(grab));
     }
 
     pub fn set_title(&self, title: &str) {
-        let _ = self
-            .proxy
-            .send_event(WindowEvent(SetTitle(String::from(title))));
+        let _ = self.send_window_event(SetTitle(String::from(title)));
     }
 
     pub fn set_decorations(&self, decoration: bool) {
-        let _ = self
-            .proxy
-            .send_event(WindowEvent(SetDecorations(decoration)));
+        let _ = self.send_window_event(SetDecorations(decoration));
     }
 
     pub fn devtool(&self) {
-        let _ = self.proxy.send_event(WindowEvent(DevTool));
+        let _ = self.send_window_event(DevTool);
     }
 
     pub fn eval(&self, script: impl std::string::ToString) {
-        let _ = self.proxy.send_event(WindowEvent(Eval(script.to_string())));
+        let _ = self.send_window_event(Eval(script.to_string()));
     }
 
     pub fn rerender(&self) {
-        self.proxy
-            .send_event(UiEvent::WindowEvent(Rerender))
-            .unwrap();
+        self.send_window_event(Rerender).unwrap();
     }
 }
@@ -1,34 +1,50 @@
 //! Includes events coming from UI to ECS runtime and to communicate bewtween systems
 
-use crate::converter;
+use crate::{
+    context::UiEventProxy,
+    converter,
+    ipc::{reply_script, EvalResponse, IpcHandlers},
+};
 use bevy::{
     input::{keyboard::KeyboardInput, ButtonState},
-    window::{ReceivedCharacter, WindowId},
+    window::{ReceivedCharacter, WindowDescriptor, WindowId},
 };
-use dioxus_core::{ElementId, EventPriority, UserEvent};
+use dioxus_core::{ElementId, EventPriority, SchedulerMsg, UserEvent};
+use dip_core::task::AsyncActionFailed;
+use futures_channel::mpsc::UnboundedSender;
 use serde::Deserialize;
 use serde_json::Value;
 use serde_repr::*;
-use std::fmt::Debug;
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 /// Tao events that emit from UI side
 #[derive(Debug)]
 pub enum UiEvent<UiAction: Debug, AsyncAction> {
     /// UI events regards window manipulation
-    WindowEvent(WindowEvent),
+    WindowEvent(WindowId, WindowEvent),
     /// User defined UiAction coming from Ui
     UiAction(UiAction),
     /// KeyboardEvent which dispatched from `window.document`. Make sure to pass `keyboard_event:
     /// true` to `DioxusSettings`.
-    KeyboardEvent(KeyboardEvent),
+    KeyboardEvent(WindowId, KeyboardEvent),
     /// User defined AsyncAction
     AsyncAction(AsyncAction),
+    /// AsyncAction failed to deliver its action
+    AsyncActionFailed(AsyncActionFailed),
+    /// Result of [`EvalRequest`](crate::ipc::EvalRequest) sent back from JavaScript
+    EvalResponse(EvalResponse),
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub(crate) struct IpcMessage {
     method: String,
     params: serde_json::Value,
+    #[serde(default)]
+    id: Option<u64>,
 }
 
 impl IpcMessage {
@@ -40,6 +56,10 @@ impl IpcMessage {
         self.params
     }
 
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
     pub fn from_payload(payload: &str) -> Option<IpcMessage> {
         match serde_json::from_str(payload) {
             Ok(message) => Some(message),
@@ -51,6 +71,89 @@ impl IpcMessage {
     }
 }
 
+/// Dispatch IPC message coming from the webview (or injected by headless tests).
+pub(crate) fn handle_ipc_message<UiAction, AsyncAction>(
+    window_id: WindowId,
+    payload: &str,
+    dom_tx: &UnboundedSender<SchedulerMsg>,
+    proxy: &UiEventProxy<UiAction, AsyncAction>,
+    is_ready: &AtomicBool,
+    ipc_handlers: &IpcHandlers,
+) where
+    UiAction: Debug,
+    AsyncAction: Debug,
+{
+    IpcMessage::from_payload(payload)
+        .map(|message| match message.method() {
+            "user_event" => {
+                let event = trigger_from_serialized(message.params());
+                log::trace!("IpcMessage user_event: {event:?}");
+                dom_tx.unbounded_send(SchedulerMsg::Event(event)).unwrap();
+            }
+            "keyboard_event" => {
+                log::trace!("IpcMessage: keyboard_event");
+                let event = KeyboardEvent::from_value(message.params());
+                proxy
+                    .send_event(UiEvent::KeyboardEvent(window_id, event))
+                    .unwrap();
+            }
+            "initialize" => {
+                log::trace!("IpcMessage: initialize");
+                is_ready.store(true, Ordering::Relaxed);
+                let _ = proxy.send_event(UiEvent::WindowEvent(window_id, WindowEvent::Rerender));
+            }
+            "browser_open" => {
+                log::trace!("IpcMessage: browser_open");
+                let data = message.params();
+                log::trace!("Open browser: {:?}", data);
+                if let Some(temp) = data.as_object() {
+                    if temp.contains_key("href") {
+                        let url = temp.get("href").unwrap().as_str().unwrap();
+                        if let Err(e) = webbrowser::open(url) {
+                            log::error!("Open Browser error: {:?}", e);
+                        }
+                    }
+                }
+            }
+            "eval_result" => {
+                log::trace!("IpcMessage: eval_result");
+                if let Some(response) = EvalResponse::from_value(window_id, message.params()) {
+                    let _ = proxy.send_event(UiEvent::EvalResponse(response));
+                }
+            }
+            method => {
+                let method = method.to_string();
+                log::trace!("IpcMessage: {method}");
+                let id = message.id();
+                match ipc_handlers.call(&method, message.params()) {
+                    Some(result) => {
+                        if let Some(id) = id {
+                            let _ = proxy.send_event(UiEvent::WindowEvent(
+                                window_id,
+                                WindowEvent::Eval(reply_script(id, result)),
+                            ));
+                        }
+                    }
+                    None => {
+                        log::warn!("No IPC handler is registered for method: {method}");
+                        if let Some(id) = id {
+                            let _ = proxy.send_event(UiEvent::WindowEvent(
+                                window_id,
+                                WindowEvent::Eval(reply_script(
+                                    id,
+                                    Err(format!("Unknown IPC method: {method}")),
+                                )),
+                            ));
+                        }
+                    }
+                }
+            }
+        })
+        .unwrap_or_else(|| {
+            log::warn!("invalid IPC message received");
+        })
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct ImEvent {
     event: String,
@@ -129,6 +232,50 @@ pub enum WindowEvent {
     Eval(String),
 }
 
+/// Create new window which renders root component registered with
+/// [`DesktopPlugin::add_root`](crate::plugin::DesktopPlugin::add_root)
+pub struct CreateRootWindow {
+    /// Window id
+    pub id: WindowId,
+    /// Window settings
+    pub descriptor: WindowDescriptor,
+    /// Name of the registered root component
+    pub root: String,
+    /// Props for root component. Registered default props are used when this is `None`.
+    pub props: Option<Box<dyn Any + Send + Sync>>,
+}
+
+impl CreateRootWindow {
+    /// Create window event with new window id
+    pub fn new(root: impl Into<String>, descriptor: WindowDescriptor) -> Self {
+        Self {
+            id: WindowId::new(),
+            descriptor,
+            root: root.into(),
+            props: None,
+        }
+    }
+
+    /// Override default props of the root component
+    pub fn with_props<RootProps>(mut self, props: RootProps) -> Self
+    where
+        RootProps: 'static + Send + Sync,
+    {
+        self.props = Some(Box::new(props));
+        self
+    }
+}
+
+impl Debug for CreateRootWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreateRootWindow")
+            .field("id", &self.id)
+            .field("descriptor", &self.descriptor)
+            .field("root", &self.root)
+            .finish()
+    }
+}
+
 /// Rust representation of web KeyboardEvent
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type")]
@@ -186,9 +333,7 @@ impl KeyboardEvent {
         }
     }
 
-    pub(crate) fn try_to_char(&self) -> Option<ReceivedCharacter> {
-        let id = WindowId::primary();
-
+    pub(crate) fn try_to_char(&self, id: WindowId) -> Option<ReceivedCharacter> {
         match self.key() {
             "Enter" => Some(ReceivedCharacter { id, char: '\r' }),
             "Backspace" => Some(ReceivedCharacter { id, char: '\u{7f}' }),
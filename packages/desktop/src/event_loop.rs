@@ -1,5 +1,6 @@
 use crate::{
-    event::{KeyboardEvent, UiEvent, WindowEvent},
+    event::{CreateRootWindow, KeyboardEvent, UiEvent, WindowEvent},
+    ipc::EvalResponse,
     setting::{DesktopSettings, UpdateMode},
     window::DioxusWindows,
 };
@@ -19,6 +20,7 @@ use bevy::{
         WindowId, WindowMode, WindowMoved, WindowResized, WindowScaleFactorChanged, Windows,
     },
 };
+use dip_core::task::AsyncActionFailed;
 use std::fmt::Debug;
 use wry::application::{
     dpi::LogicalSize,
@@ -38,6 +40,7 @@ where
         .unwrap();
 
     let mut tao_state = TaoPersistentState::default();
+    let mut create_window_event_reader = ManualEventReader::<CreateWindow>::default();
 
     event_loop.run(
         move |event: Event<UiEvent<UiAction, AsyncAction>>,
@@ -233,11 +236,15 @@ where
                 Event::UserEvent(user_event) => {
                     tao_state.prevent_app_update = false;
                     match user_event {
-                        UiEvent::WindowEvent(window_event) => {
+                        UiEvent::WindowEvent(id, window_event) => {
                             let world = app.world.cell();
-                            let mut windows = world.get_non_send_resource_mut::<Windows>().unwrap();
-                            let window = windows.get_primary_mut().unwrap();
-                            let id = WindowId::primary();
+                            let mut windows = world.get_resource_mut::<Windows>().unwrap();
+                            let window = if let Some(window) = windows.get_mut(id) {
+                                window
+                            } else {
+                                info!("Skipped event for closed window: {:?}", id);
+                                return;
+                            };
 
                             let mut dioxus_windows =
                                 world.get_non_send_resource_mut::<DioxusWindows>().unwrap();
@@ -344,28 +351,16 @@ where
                                 .expect("Provide AsyncAction event to bevy");
                             events.send(action);
                         }
-                        UiEvent::KeyboardEvent(event) => {
-                            let mut keyboard_events = app
-                                .world
-                                .get_resource_mut::<Events<KeyboardEvent>>()
-                                .unwrap();
-                            keyboard_events.send(event.clone());
-                            let mut keyboard_input_events = app
-                                .world
-                                .get_resource_mut::<Events<KeyboardInput>>()
-                                .unwrap();
-                            keyboard_input_events.send(event.to_input());
-
-                            match event.try_to_char() {
-                                Some(c) => {
-                                    let mut received_character_events = app
-                                        .world
-                                        .get_resource_mut::<Events<ReceivedCharacter>>()
-                                        .unwrap();
-                                    received_character_events.send(c);
-                                }
-                                None => {}
-                            }
+                        UiEvent::KeyboardEvent(id, event) => {
+                            send_keyboard_event(&mut app.world, id, event);
+                        }
+                        UiEvent::AsyncActionFailed(failed) => {
+                            let mut events = app.world.resource_mut::<Events<AsyncActionFailed>>();
+                            events.send(failed);
+                        }
+                        UiEvent::EvalResponse(response) => {
+                            let mut events = app.world.resource_mut::<Events<EvalResponse>>();
+                            events.send(response);
                         }
                     };
                 }
@@ -386,7 +381,10 @@ where
                     tao_state.active = true;
                 }
                 Event::MainEventsCleared => {
-                    handle_create_window_events::<UiAction, AsyncAction, RootProps>(&mut app.world);
+                    handle_create_window_events::<UiAction, AsyncAction, RootProps>(
+                        &mut app.world,
+                        &mut create_window_event_reader,
+                    );
                     let desktop_settings =
                         app.world.non_send_resource::<DesktopSettings<RootProps>>();
                     let update = if !tao_state.active {
@@ -456,8 +454,10 @@ where
     );
 }
 
-fn handle_create_window_events<UiAction, AsyncAction, RootProps>(world: &mut World)
-where
+fn handle_create_window_events<UiAction, AsyncAction, RootProps>(
+    world: &mut World,
+    create_window_events_reader: &mut ManualEventReader<CreateWindow>,
+) where
     UiAction: 'static + Send + Sync + Clone + Debug,
     AsyncAction: 'static + Send + Sync + Clone + Debug,
     RootProps: 'static + Send + Sync + Clone,
@@ -466,11 +466,9 @@ where
     let mut dioxus_windows = world.get_non_send_resource_mut::<DioxusWindows>().unwrap();
     let mut windows = world.get_resource_mut::<Windows>().unwrap();
     let create_window_events = world.get_resource::<Events<CreateWindow>>().unwrap();
-    let mut create_window_events_reader = ManualEventReader::<CreateWindow>::default();
     let mut window_created_events = world.get_resource_mut::<Events<WindowCreated>>().unwrap();
 
     for create_window_event in create_window_events_reader.iter(&create_window_events) {
-        warn!("Multiple Windows isn't supported yet!");
         let window = dioxus_windows.create::<UiAction, AsyncAction, RootProps>(
             &world,
             create_window_event.id,
@@ -481,6 +479,45 @@ where
             id: create_window_event.id,
         });
     }
+
+    let mut create_root_window_events = world
+        .get_resource_mut::<Events<CreateRootWindow>>()
+        .unwrap();
+    for create_root_window_event in create_root_window_events.drain() {
+        let CreateRootWindow {
+            id,
+            descriptor,
+            root,
+            props,
+        } = create_root_window_event;
+
+        if let Some(window) = dioxus_windows.create_with_root::<UiAction, AsyncAction, RootProps>(
+            &world,
+            id,
+            &descriptor,
+            Some((&root, props)),
+        ) {
+            windows.add(window);
+            window_created_events.send(WindowCreated { id });
+        }
+    }
+}
+
+pub(crate) fn send_keyboard_event(world: &mut World, id: WindowId, event: KeyboardEvent) {
+    let mut keyboard_events = world.get_resource_mut::<Events<KeyboardEvent>>().unwrap();
+    keyboard_events.send(event.clone());
+    let mut keyboard_input_events = world.get_resource_mut::<Events<KeyboardInput>>().unwrap();
+    keyboard_input_events.send(event.to_input());
+
+    match event.try_to_char(id) {
+        Some(c) => {
+            let mut received_character_events = world
+                .get_resource_mut::<Events<ReceivedCharacter>>()
+                .unwrap();
+            received_character_events.send(c);
+        }
+        None => {}
+    }
 }
 
 struct TaoPersistentState {
@@ -0,0 +1,392 @@
+//! Run desktop app without window for testing
+#![allow(non_snake_case)]
+
+use crate::{
+    context::UiEventProxy,
+    event::{handle_ipc_message, KeyboardEvent, UiEvent, WindowEvent},
+    event_loop::send_keyboard_event,
+    ipc::{EvalRequest, EvalResponse, IpcHandlers},
+    setting::DesktopSettings,
+    virtual_dom::{UiStateBroadcast, VirtualDomHandle, VirtualDomSpawner},
+};
+use bevy::{
+    app::{App, CoreStage, Plugin},
+    ecs::{
+        event::{EventReader, Events},
+        system::{IntoExclusiveSystem, Res},
+        world::World,
+    },
+    input::InputPlugin,
+    utils::{Duration, Instant},
+    window::{WindowId, WindowPlugin},
+};
+use dioxus_core::Component as DioxusComponent;
+use dip_core::{
+    runtime::async_runtime,
+    schedule::UiSchedulePlugin,
+    task::{AsyncActionFailed, AsyncActionPool},
+    ui_state::UiStateHandler,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::{select, sync::mpsc};
+
+/// Headless variant of [`DesktopPlugin`](crate::plugin::DesktopPlugin).
+///
+/// Runs the same VirtualDom, UiState dispatch and UiAction/AsyncAction channels without creating
+/// an event loop, a window or a webview. Use [`HeadlessWindow`] resource to inspect rendered edits
+/// and to inject IPC messages.
+pub struct HeadlessDesktopPlugin<UiState, UiAction, AsyncAction, RootProps = ()> {
+    /// Root component
+    pub Root: DioxusComponent<RootProps>,
+
+    ipc_handlers: IpcHandlers,
+    ui_state_type: PhantomData<UiState>,
+    ui_action_type: PhantomData<UiAction>,
+    async_action_type: PhantomData<AsyncAction>,
+}
+
+impl<UiState, UiAction, AsyncAction, RootProps> Plugin
+    for HeadlessDesktopPlugin<UiState, UiAction, AsyncAction, RootProps>
+where
+    UiState: 'static + Send + Sync + Clone + UiStateHandler,
+    UiAction: 'static + Send + Sync + Clone + Debug,
+    AsyncAction: 'static + Send + Sync + Clone + Debug,
+    RootProps: 'static + Send + Sync + Clone + Default,
+{
+    fn build(&self, app: &mut App) {
+        let (ui_state_tx, ui_state_rx) = mpsc::channel::<UiState>(8);
+        let (ui_action_tx, mut ui_action_rx) = mpsc::channel::<UiAction>(8);
+        let (async_action_tx, mut async_action_rx) = mpsc::channel::<AsyncAction>(8);
+        let (ui_event_tx, ui_event_rx) =
+            mpsc::unbounded_channel::<UiEvent<UiAction, AsyncAction>>();
+        let (async_action_failed_tx, mut async_action_failed_rx) =
+            mpsc::unbounded_channel::<AsyncActionFailed>();
+        let runtime = async_runtime(&mut app.world);
+        let async_action = AsyncActionPool::new(
+            runtime.clone(),
+            async_action_tx.clone(),
+            async_action_failed_tx,
+        );
+
+        let settings = app
+            .world
+            .remove_non_send_resource::<DesktopSettings<RootProps>>()
+            .unwrap_or_default();
+
+        let proxy = UiEventProxy::Channel(ui_event_tx.clone());
+
+        let virtual_dom = VirtualDomSpawner::new(
+            runtime.clone(),
+            self.Root,
+            settings.root_props.as_ref().unwrap().clone(),
+            UiStateBroadcast::new(&runtime, ui_state_rx),
+            ui_action_tx,
+        )
+        .spawn(WindowId::primary(), proxy.clone());
+
+        runtime.spawn(async move {
+            loop {
+                let event = select! {
+                    action = ui_action_rx.recv() => {
+                        log::trace!("UiAction: {:#?}", action);
+                        action.map(UiEvent::UiAction)
+                    }
+                    action = async_action_rx.recv() => {
+                        log::trace!("AsyncAction: {:#?}", action);
+                        action.map(UiEvent::AsyncAction)
+                    }
+                    failed = async_action_failed_rx.recv() => {
+                        log::trace!("AsyncActionFailed: {:#?}", failed);
+                        failed.map(UiEvent::AsyncActionFailed)
+                    }
+                };
+
+                // Channels are closed once app is dropped
+                match event {
+                    Some(event) if proxy.send_event(event).is_ok() => {}
+                    _ => break,
+                }
+            }
+        });
+
+        let headless_window =
+            HeadlessWindow::new(ui_event_tx, virtual_dom, self.ipc_handlers.clone());
+
+        app.add_plugin(WindowPlugin {
+            add_primary_window: false,
+            exit_on_all_closed: false,
+            ..Default::default()
+        })
+        .add_plugin(UiSchedulePlugin)
+        .add_plugin(InputPlugin)
+        .add_event::<KeyboardEvent>()
+        .add_event::<AsyncActionFailed>()
+        .add_event::<EvalRequest>()
+        .add_event::<EvalResponse>()
+        .insert_resource(self.ipc_handlers.clone())
+        .insert_resource(async_action)
+        .insert_resource(ui_state_tx)
+        .insert_resource(headless_window)
+        .insert_non_send_resource(settings)
+        .insert_non_send_resource(ui_event_rx)
+        .add_system_to_stage(
+            CoreStage::First,
+            handle_ui_events::<UiAction, AsyncAction>.exclusive_system(),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            eval_requested_scripts::<UiAction, AsyncAction>,
+        );
+    }
+}
+
+impl<UiState, UiAction, AsyncAction, RootProps>
+    HeadlessDesktopPlugin<UiState, UiAction, AsyncAction, RootProps>
+where
+    UiState: Send + Sync + UiStateHandler,
+    UiAction: Clone + Debug + Send + Sync,
+    RootProps: Send + Sync + Clone + 'static,
+{
+    /// Initialize HeadlessDesktopPlugin with root component and channel types
+    ///
+    /// ```no_run
+    /// use dip::{bevy::utils::Duration, prelude::*};
+    ///
+    /// let mut app = App::new();
+    /// app.add_plugin(HeadlessDesktopPlugin::<NoUiState, NoUiAction, NoAsyncAction>::new(Root));
+    ///
+    /// let window = app.world.resource::<HeadlessWindow>().clone();
+    /// assert!(window.update_until_rendered(&mut app, Duration::from_secs(1)));
+    /// assert!(window.edits().iter().any(|edit| edit.contains("Hello, World !")));
+    ///
+    /// fn Root(cx: Scope) -> Element {
+    ///    cx.render(rsx! {
+    ///    h1 { "Hello, World !" }
+    ///        })
+    /// }
+    /// ```
+    pub fn new(Root: DioxusComponent<RootProps>) -> Self {
+        Self {
+            Root,
+            ipc_handlers: IpcHandlers::default(),
+            ui_state_type: PhantomData,
+            ui_action_type: PhantomData,
+            async_action_type: PhantomData,
+        }
+    }
+
+    /// Register Rust handler for IPC method. See
+    /// [`DesktopPlugin::ipc_handler`](crate::plugin::DesktopPlugin::ipc_handler).
+    pub fn ipc_handler<Req, Res, E, F>(self, method: &str, handler: F) -> Self
+    where
+        Req: DeserializeOwned,
+        Res: Serialize,
+        E: Display,
+        F: Fn(Req) -> Result<Res, E> + Send + Sync + 'static,
+    {
+        self.ipc_handlers.register(method, handler);
+        self
+    }
+}
+
+/// Handle to drive and inspect headless VirtualDom
+pub struct HeadlessWindow<UiAction: Debug + 'static = (), AsyncAction: 'static = ()> {
+    ui_event_tx: mpsc::UnboundedSender<UiEvent<UiAction, AsyncAction>>,
+    virtual_dom: Arc<VirtualDomHandle>,
+    edits: Arc<Mutex<Vec<String>>>,
+    render_count: Arc<AtomicUsize>,
+    is_ready: Arc<AtomicBool>,
+    ipc_handlers: IpcHandlers,
+    scripts: Arc<Mutex<Vec<String>>>,
+}
+
+impl<UiAction, AsyncAction> Clone for HeadlessWindow<UiAction, AsyncAction>
+where
+    UiAction: Debug,
+{
+    fn clone(&self) -> Self {
+        Self {
+            ui_event_tx: self.ui_event_tx.clone(),
+            virtual_dom: self.virtual_dom.clone(),
+            edits: self.edits.clone(),
+            render_count: self.render_count.clone(),
+            is_ready: self.is_ready.clone(),
+            ipc_handlers: self.ipc_handlers.clone(),
+            scripts: self.scripts.clone(),
+        }
+    }
+}
+
+impl<UiAction, AsyncAction> HeadlessWindow<UiAction, AsyncAction>
+where
+    UiAction: Debug,
+    AsyncAction: Debug,
+{
+    fn new(
+        ui_event_tx: mpsc::UnboundedSender<UiEvent<UiAction, AsyncAction>>,
+        virtual_dom: VirtualDomHandle,
+        ipc_handlers: IpcHandlers,
+    ) -> Self {
+        Self {
+            ui_event_tx,
+            virtual_dom: Arc::new(virtual_dom),
+            edits: Arc::new(Mutex::new(Vec::new())),
+            render_count: Arc::new(AtomicUsize::new(0)),
+            is_ready: Arc::new(AtomicBool::new(true)),
+            ipc_handlers,
+            scripts: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Serialized edits that have been applied so far, in order
+    pub fn edits(&self) -> Vec<String> {
+        self.edits.lock().unwrap().clone()
+    }
+
+    /// Take applied edits out and start recording from empty list
+    pub fn take_edits(&self) -> Vec<String> {
+        self.edits.lock().unwrap().drain(..).collect()
+    }
+
+    /// Number of rerender requests handled so far
+    pub fn render_count(&self) -> usize {
+        self.render_count.load(Ordering::Relaxed)
+    }
+
+    /// Inject raw IPC payload as if it is posted from `window.ipc.postMessage`
+    pub fn send_ipc(&self, payload: &str) {
+        let proxy = UiEventProxy::Channel(self.ui_event_tx.clone());
+        handle_ipc_message(
+            WindowId::primary(),
+            payload,
+            &self.virtual_dom.scheduler_tx,
+            &proxy,
+            &self.is_ready,
+            &self.ipc_handlers,
+        );
+    }
+
+    /// Inject `user_event` IPC message. `params` is what Dioxus interpreter sends: `{ event,
+    /// mounted_dom_id, contents }`.
+    pub fn send_user_event(&self, params: serde_json::Value) {
+        let payload = serde_json::json!({ "method": "user_event", "params": params });
+        self.send_ipc(&payload.to_string());
+    }
+
+    /// Take scripts that would have been evaluated in the webview, e.g. IPC replies and scripts
+    /// of [`EvalRequest`]. Answer the latter with `eval_result` message through
+    /// [`send_ipc`](Self::send_ipc).
+    pub fn take_scripts(&self) -> Vec<String> {
+        self.scripts.lock().unwrap().drain(..).collect()
+    }
+
+    /// Keep updating app until VirtualDom renders next edits or timeout is reached. Returns
+    /// `false` on timeout.
+    pub fn update_until_rendered(&self, app: &mut App, timeout: Duration) -> bool {
+        let start = Instant::now();
+        let render_count = self.render_count();
+
+        while start.elapsed() < timeout {
+            app.update();
+            if self.render_count() > render_count {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        false
+    }
+
+    fn rerender(&self) {
+        let mut queue = self.virtual_dom.edit_queue.lock().unwrap();
+        self.edits.lock().unwrap().extend(queue.drain(..));
+        self.render_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn handle_ui_events<UiAction, AsyncAction>(world: &mut World)
+where
+    UiAction: 'static + Send + Sync + Clone + Debug,
+    AsyncAction: 'static + Send + Sync + Clone + Debug,
+{
+    let mut events = vec![];
+    {
+        let mut ui_event_rx = world
+            .non_send_resource_mut::<mpsc::UnboundedReceiver<UiEvent<UiAction, AsyncAction>>>();
+        while let Ok(event) = ui_event_rx.try_recv() {
+            events.push(event);
+        }
+    }
+
+    for event in events {
+        match event {
+            UiEvent::WindowEvent(_, WindowEvent::Rerender) => {
+                world
+                    .resource::<HeadlessWindow<UiAction, AsyncAction>>()
+                    .rerender();
+            }
+            UiEvent::WindowEvent(_, WindowEvent::Eval(script)) => {
+                world
+                    .resource::<HeadlessWindow<UiAction, AsyncAction>>()
+                    .scripts
+                    .lock()
+                    .unwrap()
+                    .push(script);
+            }
+            UiEvent::WindowEvent(_, window_event) => {
+                log::trace!("Skipped window event in headless mode: {window_event:?}");
+            }
+            UiEvent::UiAction(action) => {
+                let mut events = world
+                    .get_resource_mut::<Events<UiAction>>()
+                    .expect("Provide UiAction event to bevy");
+                events.send(action);
+            }
+            UiEvent::AsyncAction(action) => {
+                let mut events = world
+                    .get_resource_mut::<Events<AsyncAction>>()
+                    .expect("Provide AsyncAction event to bevy");
+                events.send(action);
+            }
+            UiEvent::KeyboardEvent(id, event) => {
+                send_keyboard_event(world, id, event);
+            }
+            UiEvent::AsyncActionFailed(failed) => {
+                world
+                    .resource_mut::<Events<AsyncActionFailed>>()
+                    .send(failed);
+            }
+            UiEvent::EvalResponse(response) => {
+                world.resource_mut::<Events<EvalResponse>>().send(response);
+            }
+        }
+    }
+}
+
+// Scripts are kept in HeadlessWindow instead of being evaluated in webview
+fn eval_requested_scripts<UiAction, AsyncAction>(
+    window: Res<HeadlessWindow<UiAction, AsyncAction>>,
+    mut eval_requests: EventReader<EvalRequest>,
+) where
+    UiAction: 'static + Send + Sync + Clone + Debug,
+    AsyncAction: 'static + Send + Sync + Clone + Debug,
+{
+    for request in eval_requests.iter() {
+        if request.window_id == WindowId::primary() {
+            window.scripts.lock().unwrap().push(request.to_script());
+        } else {
+            log::warn!(
+                "Skipped eval request for unknown window: {:?}",
+                request.window_id
+            );
+        }
+    }
+}
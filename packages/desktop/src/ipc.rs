@@ -0,0 +1,226 @@
+//! Typed request/response bridge between ECS systems and JavaScript
+
+use bevy::{utils::HashMap, window::WindowId};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::{self, Debug, Display},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+/// JavaScript installed on every webview. `window.dip.invoke(method, params)` posts IPC message
+/// and returns Promise resolved with reply from registered Rust handler.
+pub(crate) const IPC_BRIDGE_JS: &str = r#"
+    window.dip = {
+      __nextId: 0,
+      __pending: new Map(),
+      invoke(method, params = {}) {
+        const id = ++this.__nextId;
+        return new Promise((resolve, reject) => {
+          this.__pending.set(id, { resolve, reject });
+          window.ipc.postMessage(JSON.stringify({ method, params, id }));
+        });
+      },
+      __reply(id, ok, value) {
+        const pending = this.__pending.get(id);
+        if (!pending) return;
+        this.__pending.delete(id);
+        ok ? pending.resolve(value) : pending.reject(new Error(value));
+      },
+    };
+"#;
+
+/// Correlation id shared between request and its response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IpcId(u64);
+
+impl IpcId {
+    /// Generate unique id
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for IpcId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for IpcId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Evaluate JavaScript in a window and receive its result as [`EvalResponse`] event.
+///
+/// Script is treated as a body of async function, so use `return` to send value back.
+///
+/// ```no_run
+/// use dip::{bevy::window::WindowId, prelude::*};
+///
+/// fn request_title(mut events: EventWriter<EvalRequest>) {
+///     events.send(EvalRequest::new(WindowId::primary(), "return document.title"));
+/// }
+///
+/// fn receive_title(mut events: EventReader<EvalResponse>) {
+///     for response in events.iter() {
+///         let title = response.parse::<String>();
+///         println!("{}: {title:?}", response.id);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EvalRequest {
+    /// Correlation id which is copied to the response
+    pub id: IpcId,
+    /// Window to evaluate script
+    pub window_id: WindowId,
+    /// JavaScript function body
+    pub script: String,
+}
+
+impl EvalRequest {
+    /// Create request with new correlation id
+    pub fn new(window_id: WindowId, script: impl Into<String>) -> Self {
+        Self {
+            id: IpcId::new(),
+            window_id,
+            script: script.into(),
+        }
+    }
+
+    pub(crate) fn to_script(&self) -> String {
+        format!(
+            r#"
+            (async () => {{ {script} }})()
+              .then((value) => window.ipc.postMessage(JSON.stringify({{
+                method: "eval_result",
+                params: {{ id: {id}, value: value === undefined ? null : value }},
+              }})))
+              .catch((e) => window.ipc.postMessage(JSON.stringify({{
+                method: "eval_result",
+                params: {{ id: {id}, error: String(e) }},
+              }})));
+            "#,
+            script = self.script,
+            id = self.id,
+        )
+    }
+}
+
+/// Result of [`EvalRequest`]
+#[derive(Debug, Clone)]
+pub struct EvalResponse {
+    /// Correlation id of the request
+    pub id: IpcId,
+    /// Window where script is evaluated
+    pub window_id: WindowId,
+    /// Returned value or thrown error message
+    pub result: Result<Value, String>,
+}
+
+impl EvalResponse {
+    pub(crate) fn from_value(window_id: WindowId, params: Value) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct EvalResult {
+            id: IpcId,
+            #[serde(default)]
+            value: Value,
+            error: Option<String>,
+        }
+
+        match serde_json::from_value::<EvalResult>(params) {
+            Ok(EvalResult { id, value, error }) => Some(Self {
+                id,
+                window_id,
+                result: match error {
+                    Some(error) => Err(error),
+                    None => Ok(value),
+                },
+            }),
+            Err(e) => {
+                log::error!("could not parse eval result, error: {e}");
+                None
+            }
+        }
+    }
+
+    /// Deserialize returned value
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, String> {
+        match &self.result {
+            Ok(value) => serde_json::from_value(value.clone()).map_err(|e| e.to_string()),
+            Err(e) => Err(e.clone()),
+        }
+    }
+}
+
+type IpcHandlerFn = Box<dyn Fn(Value) -> Result<Value, String> + Send + Sync>;
+
+/// Rust handlers for IPC methods called from JavaScript with `window.dip.invoke(method, params)`
+///
+/// Handlers can be registered with [`DesktopPlugin::ipc_handler`](crate::plugin::DesktopPlugin::ipc_handler)
+/// or later on from systems through `Res<IpcHandlers>`.
+#[derive(Clone, Default)]
+pub struct IpcHandlers {
+    handlers: Arc<RwLock<HashMap<String, IpcHandlerFn>>>,
+}
+
+impl IpcHandlers {
+    /// Register handler for IPC method. Params are deserialized into `Req` and `Ok` value is sent
+    /// back to JavaScript. `Err` rejects the Promise with its message.
+    pub fn register<Req, Res, E, F>(&self, method: impl Into<String>, handler: F)
+    where
+        Req: DeserializeOwned,
+        Res: Serialize,
+        E: Display,
+        F: Fn(Req) -> Result<Res, E> + Send + Sync + 'static,
+    {
+        let handler = move |params: Value| {
+            let req = serde_json::from_value(params).map_err(|e| e.to_string())?;
+            let res = handler(req).map_err(|e| e.to_string())?;
+            serde_json::to_value(res).map_err(|e| e.to_string())
+        };
+
+        self.handlers
+            .write()
+            .unwrap()
+            .insert(method.into(), Box::new(handler));
+    }
+
+    /// Remove handler for IPC method
+    pub fn unregister(&self, method: &str) {
+        self.handlers.write().unwrap().remove(method);
+    }
+
+    /// Call handler. Returns `None` when method is not registered.
+    pub(crate) fn call(&self, method: &str, params: Value) -> Option<Result<Value, String>> {
+        self.handlers
+            .read()
+            .unwrap()
+            .get(method)
+            .map(|handler| handler(params))
+    }
+}
+
+impl Debug for IpcHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IpcHandlers")
+            .field("methods", &self.handlers.read().unwrap().keys())
+            .finish()
+    }
+}
+
+/// Script to resolve or reject Promise returned from `window.dip.invoke`
+pub(crate) fn reply_script(id: u64, result: Result<Value, String>) -> String {
+    let (ok, value) = match result {
+        Ok(value) => (true, value),
+        Err(e) => (false, Value::String(e)),
+    };
+    format!("window.dip.__reply({id}, {ok}, {value})")
+}
@@ -8,7 +8,9 @@ mod context;
 mod converter;
 pub mod event;
 mod event_loop;
+pub mod headless;
 pub mod hooks;
+pub mod ipc;
 pub mod plugin;
 mod protocol;
 pub mod setting;
@@ -21,7 +23,9 @@ pub mod prelude {
 
     pub use crate::{
         event::*,
+        headless::{HeadlessDesktopPlugin, HeadlessWindow},
         hooks::*,
+        ipc::{EvalRequest, EvalResponse, IpcHandlers, IpcId},
         plugin::DesktopPlugin,
         setting::{DesktopSettings, UpdateMode},
     };
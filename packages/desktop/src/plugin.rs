@@ -2,12 +2,12 @@
 #![allow(non_snake_case)]
 
 use crate::{
-    context::UiContext,
-    event::{KeyboardEvent, UiEvent},
+    event::{CreateRootWindow, KeyboardEvent, UiEvent},
     event_loop::start_event_loop,
+    ipc::{EvalRequest, EvalResponse, IpcHandlers},
     setting::DesktopSettings,
-    system::change_window,
-    virtual_dom::VirtualDom,
+    system::{change_window, eval_requested_scripts},
+    virtual_dom::{UiStateBroadcast, VirtualDomSpawner},
     window::DioxusWindows,
 };
 
@@ -17,18 +17,37 @@ use bevy::{
     input::InputPlugin,
     window::{CreateWindow, ModifiesWindows, WindowCreated, WindowPlugin, Windows},
 };
-use dioxus_core::{Component as DioxusComponent, SchedulerMsg};
-use dip_core::{schedule::UiSchedulePlugin, task::AsyncActionPool, ui_state::UiStateHandler};
-use futures_channel::mpsc as futures_mpsc;
-use std::{fmt::Debug, marker::PhantomData, sync::Arc, sync::Mutex};
-use tokio::{runtime::Runtime, select, sync::mpsc};
+use dioxus_core::Component as DioxusComponent;
+use dip_core::{
+    runtime::async_runtime,
+    schedule::UiSchedulePlugin,
+    task::{AsyncActionFailed, AsyncActionPool},
+    ui_state::UiStateHandler,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+use tokio::{select, sync::mpsc};
 use wry::application::event_loop::EventLoop;
 
+type AddRoot<UiState, UiAction, AsyncAction> = Box<
+    dyn Fn(
+            &mut VirtualDomSpawner<UiAction, AsyncAction>,
+            &UiStateBroadcast<UiState>,
+            &mpsc::Sender<UiAction>,
+        ) + Send
+        + Sync,
+>;
+
 /// Dioxus Plugin for Bevy
-pub struct DesktopPlugin<UiState, UiAction, AsyncAction, RootProps = ()> {
+pub struct DesktopPlugin<UiState, UiAction: Debug + 'static, AsyncAction: 'static, RootProps = ()> {
     /// Root component
     pub Root: DioxusComponent<RootProps>,
 
+    roots: Vec<AddRoot<UiState, UiAction, AsyncAction>>,
+    ipc_handlers: IpcHandlers,
     ui_state_type: PhantomData<UiState>,
     ui_action_type: PhantomData<UiAction>,
     async_action_type: PhantomData<AsyncAction>,
@@ -37,17 +56,23 @@ pub struct DesktopPlugin<UiState, UiAction, AsyncAction, RootProps = ()> {
 impl<UiState, UiAction, AsyncAction, RootProps> Plugin
     for DesktopPlugin<UiState, UiAction, AsyncAction, RootProps>
 where
-    UiState: 'static + Send + Sync + UiStateHandler,
+    UiState: 'static + Send + Sync + Clone + UiStateHandler,
     UiAction: 'static + Send + Sync + Clone + Debug,
     AsyncAction: 'static + Send + Sync + Clone + Debug,
     RootProps: 'static + Send + Sync + Clone + Default,
 {
     fn build(&self, app: &mut App) {
-        let (vdom_scheduler_tx, vdom_scheduler_rx) = futures_mpsc::unbounded::<SchedulerMsg>();
         let (ui_state_tx, ui_state_rx) = mpsc::channel::<UiState>(8);
         let (ui_action_tx, mut ui_action_rx) = mpsc::channel::<UiAction>(8);
         let (async_action_tx, mut async_action_rx) = mpsc::channel::<AsyncAction>(8);
-        let async_action = AsyncActionPool::new(async_action_tx.clone());
+        let (async_action_failed_tx, mut async_action_failed_rx) =
+            mpsc::unbounded_channel::<AsyncActionFailed>();
+        let runtime = async_runtime(&mut app.world);
+        let async_action = AsyncActionPool::new(
+            runtime.clone(),
+            async_action_tx.clone(),
+            async_action_failed_tx,
+        );
 
         let event_loop = EventLoop::<UiEvent<UiAction, AsyncAction>>::with_user_event();
         let settings = app
@@ -56,59 +81,62 @@ where
             .unwrap_or_default();
 
         let proxy = event_loop.create_proxy();
-        let edit_queue = Arc::new(Mutex::new(Vec::new()));
 
-        let runtime = Runtime::new().unwrap();
+        let ui_state = UiStateBroadcast::new(&runtime, ui_state_rx);
+        let mut virtual_dom_spawner = VirtualDomSpawner::new(
+            runtime.clone(),
+            self.Root,
+            settings.root_props.as_ref().unwrap().clone(),
+            ui_state.clone(),
+            ui_action_tx.clone(),
+        );
+        for add_root in self.roots.iter() {
+            add_root(&mut virtual_dom_spawner, &ui_state, &ui_action_tx);
+        }
 
-        let proxy_clone = proxy.clone();
         runtime.spawn(async move {
             loop {
-                select! {
+                let event = select! {
                     action = ui_action_rx.recv() => {
                         log::trace!("UiAction: {:#?}", action);
-                        proxy_clone.send_event(UiEvent::UiAction(action.unwrap())).unwrap();
+                        action.map(UiEvent::UiAction)
                     }
                     action = async_action_rx.recv() => {
                         log::trace!("AsyncAction: {:#?}", action);
-                        proxy_clone.send_event(UiEvent::AsyncAction(action.unwrap())).unwrap();
+                        action.map(UiEvent::AsyncAction)
                     }
+                    failed = async_action_failed_rx.recv() => {
+                        log::trace!("AsyncActionFailed: {:#?}", failed);
+                        failed.map(UiEvent::AsyncActionFailed)
+                    }
+                };
+
+                // Channels are closed once app is dropped, and proxy fails once event loop exits
+                match event {
+                    Some(event) if proxy.send_event(event).is_ok() => {}
+                    _ => break,
                 }
             }
         });
 
-        let root_clone = self.Root.clone();
-        let root_props_clone = settings.root_props.as_ref().unwrap().clone();
-        let edit_queue_clone = edit_queue.clone();
-        let vdom_scheduler_tx_clone = vdom_scheduler_tx.clone();
         app.add_plugin(WindowPlugin::default())
             .add_plugin(UiSchedulePlugin)
             .add_plugin(InputPlugin)
             .add_event::<KeyboardEvent>()
+            .add_event::<AsyncActionFailed>()
+            .add_event::<CreateRootWindow>()
+            .add_event::<EvalRequest>()
+            .add_event::<EvalResponse>()
+            .insert_resource(self.ipc_handlers.clone())
             .insert_resource(async_action)
-            .insert_resource(runtime)
-            .insert_resource(vdom_scheduler_tx)
             .insert_resource(ui_state_tx)
-            .insert_resource(edit_queue)
+            .insert_resource(virtual_dom_spawner)
             .init_non_send_resource::<DioxusWindows>()
             .insert_non_send_resource(settings)
             .insert_non_send_resource(event_loop)
             .set_runner(|app| start_event_loop::<UiAction, AsyncAction, RootProps>(app))
-            .add_system_to_stage(CoreStage::PostUpdate, change_window.label(ModifiesWindows));
-
-        std::thread::spawn(move || {
-            Runtime::new().unwrap().block_on(async move {
-                let mut virtual_dom = VirtualDom::new(
-                    root_clone,
-                    root_props_clone,
-                    edit_queue_clone,
-                    (vdom_scheduler_tx_clone, vdom_scheduler_rx),
-                    ui_state_rx,
-                );
-                virtual_dom.provide_ui_context(UiContext::new(proxy.clone(), ui_action_tx));
-
-                virtual_dom.run().await;
-            });
-        });
+            .add_system_to_stage(CoreStage::PostUpdate, change_window.label(ModifiesWindows))
+            .add_system_to_stage(CoreStage::PostUpdate, eval_requested_scripts);
 
         Self::handle_initial_window_events(&mut app.world);
     }
@@ -118,7 +146,8 @@ impl<UiState, UiAction, AsyncAction, RootProps>
     DesktopPlugin<UiState, UiAction, AsyncAction, RootProps>
 where
     UiState: Send + Sync + UiStateHandler,
-    UiAction: Clone + Debug + Send + Sync,
+    UiAction: 'static + Clone + Debug + Send + Sync,
+    AsyncAction: 'static,
     RootProps: Send + Sync + Clone + 'static,
 {
     /// Initialize DioxusPlugin with root component and channel types
@@ -141,12 +170,119 @@ where
     pub fn new(Root: DioxusComponent<RootProps>) -> Self {
         Self {
             Root,
+            roots: Vec::new(),
+            ipc_handlers: IpcHandlers::default(),
             ui_state_type: PhantomData,
             ui_action_type: PhantomData,
             async_action_type: PhantomData,
         }
     }
 
+    /// Register additional root component with default props. Use [`CreateRootWindow`] event
+    /// to open new window rendering it.
+    ///
+    /// ```no_run
+    /// use dip::{bevy::window::WindowDescriptor, prelude::*};
+    ///
+    /// fn main() {
+    ///    App::new()
+    ///         .add_plugin(
+    ///             DesktopPlugin::<NoUiState, NoUiAction, NoAsyncAction>::new(Root)
+    ///                 .add_root("preferences", Preferences),
+    ///         )
+    ///         .add_startup_system(open_preferences)
+    ///         .run();
+    /// }
+    ///
+    /// fn open_preferences(mut events: EventWriter<CreateRootWindow>) {
+    ///     events.send(CreateRootWindow::new("preferences", WindowDescriptor::default()));
+    /// }
+    ///
+    /// fn Root(cx: Scope) -> Element {
+    ///    cx.render(rsx! {
+    ///    h1 { "Main" }
+    ///        })
+    /// }
+    ///
+    /// fn Preferences(cx: Scope) -> Element {
+    ///    cx.render(rsx! {
+    ///    h1 { "Preferences" }
+    ///        })
+    /// }
+    /// ```
+    pub fn add_root<Props>(self, name: &str, Root: DioxusComponent<Props>) -> Self
+    where
+        UiState: 'static + Clone,
+        AsyncAction: Send + Sync + Clone + Debug,
+        Props: 'static + Send + Sync + Clone + Default,
+    {
+        self.add_root_with_props(name, Root, Props::default())
+    }
+
+    /// Register additional root component with its props. Props can be overridden per window with
+    /// [`CreateRootWindow::with_props`].
+    pub fn add_root_with_props<Props>(
+        mut self,
+        name: &str,
+        Root: DioxusComponent<Props>,
+        props: Props,
+    ) -> Self
+    where
+        UiState: 'static + Clone,
+        AsyncAction: Send + Sync + Clone + Debug,
+        Props: 'static + Send + Sync + Clone,
+    {
+        let name = name.to_string();
+        self.roots
+            .push(Box::new(move |spawner, ui_state, ui_action_tx| {
+                spawner.add_root(
+                    name.clone(),
+                    Root,
+                    props.clone(),
+                    ui_state.clone(),
+                    ui_action_tx.clone(),
+                );
+            }));
+        self
+    }
+
+    /// Register Rust handler for IPC method called from JavaScript. Returned value is serialized
+    /// and resolves the Promise.
+    ///
+    /// ```no_run
+    /// use dip::prelude::*;
+    ///
+    /// fn main() {
+    ///    App::new()
+    ///         .add_plugin(
+    ///             DesktopPlugin::<NoUiState, NoUiAction, NoAsyncAction>::new(Root)
+    ///                 .ipc_handler("add", |(a, b): (i32, i32)| Ok::<_, String>(a + b)),
+    ///         )
+    ///         .run();
+    /// }
+    ///
+    /// fn Root(cx: Scope) -> Element {
+    ///    let eval = use_eval::<NoUiAction, NoAsyncAction, _>(&cx);
+    ///
+    ///    cx.render(rsx! {
+    ///        button {
+    ///            onclick: move |_| eval("window.dip.invoke('add', [1, 2]).then(console.log)"),
+    ///            "Add"
+    ///        }
+    ///    })
+    /// }
+    /// ```
+    pub fn ipc_handler<Req, Res, E, F>(self, method: &str, handler: F) -> Self
+    where
+        Req: DeserializeOwned,
+        Res: Serialize,
+        E: Display,
+        F: Fn(Req) -> Result<Res, E> + Send + Sync + 'static,
+    {
+        self.ipc_handlers.register(method, handler);
+        self
+    }
+
     fn handle_initial_window_events(world: &mut World)
     where
         UiAction: 'static + Send + Sync + Clone + Debug,
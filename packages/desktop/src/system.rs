@@ -1,8 +1,8 @@
-use crate::{converter, window::DioxusWindows};
+use crate::{converter, ipc::EvalRequest, window::DioxusWindows};
 use bevy::{
     ecs::{
-        event::EventWriter,
-        system::{NonSendMut, ResMut},
+        event::{EventReader, EventWriter},
+        system::{NonSend, NonSendMut, ResMut},
     },
     log::{error, warn},
     math::{UVec2, Vec2},
@@ -159,3 +159,22 @@ pub fn change_window(
         }
     }
 }
+
+pub fn eval_requested_scripts(
+    dioxus_windows: NonSend<DioxusWindows>,
+    mut eval_requests: EventReader<EvalRequest>,
+) {
+    for request in eval_requests.iter() {
+        match dioxus_windows.get(request.window_id) {
+            Some(window) => {
+                if let Err(e) = window.webview.evaluate_script(&request.to_script()) {
+                    error!("Failed to evaluate script: {e}");
+                }
+            }
+            None => warn!(
+                "Skipped eval request for closed window: {:?}",
+                request.window_id
+            ),
+        }
+    }
+}
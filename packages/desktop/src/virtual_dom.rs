@@ -1,25 +1,33 @@
 #![allow(non_snake_case)]
 
-use crate::context::UiContext;
+use crate::context::{UiContext, UiEventProxy};
+use bevy::{utils::HashMap, window::WindowId};
 use dioxus::{
     fermi::AtomRoot,
     hooks::{UnboundedReceiver, UnboundedSender},
 };
 use dioxus_core::{Component, SchedulerMsg, ScopeId, VirtualDom as DioxusVirtualDom};
-use dip_core::ui_state::UiStateHandler;
+use dip_core::{task::AsyncRuntime, ui_state::UiStateHandler};
+use futures_channel::mpsc as futures_mpsc;
 use std::{
+    any::Any,
     fmt::Debug,
     marker::PhantomData,
+    mem::{discriminant, Discriminant},
     rc::Rc,
     sync::{Arc, Mutex},
 };
-use tokio::{select, sync::mpsc::Receiver};
+use tokio::{
+    select,
+    sync::{mpsc, oneshot},
+};
 
 pub struct VirtualDom<UiState: 'static, UiAction, AsyncAction> {
     virtual_dom: DioxusVirtualDom,
     edit_queue: Arc<Mutex<Vec<String>>>,
-    ui_state_rx: Receiver<UiState>,
+    ui_state_rx: mpsc::UnboundedReceiver<UiState>,
     scheduler_tx: UnboundedSender<SchedulerMsg>,
+    shutdown_rx: oneshot::Receiver<()>,
     ui_action_type: PhantomData<UiAction>,
     async_action_type: PhantomData<AsyncAction>,
 }
@@ -38,7 +46,8 @@ where
             UnboundedSender<SchedulerMsg>,
             UnboundedReceiver<SchedulerMsg>,
         ),
-        ui_state_rx: Receiver<UiState>,
+        ui_state_rx: mpsc::UnboundedReceiver<UiState>,
+        shutdown_rx: oneshot::Receiver<()>,
     ) -> Self
     where
         RootProps: 'static,
@@ -54,6 +63,7 @@ where
             edit_queue,
             ui_state_rx,
             scheduler_tx,
+            shutdown_rx,
             ui_action_type: PhantomData,
             async_action_type: PhantomData,
         }
@@ -82,14 +92,22 @@ where
                 }
                 // 2) when Ui state is changed
                 state = self.ui_state_rx.recv() => {
-                    if let Some(state) = state {
-                        log::trace!("UiState");
-                        let root = self.atom_root();
-                        state.handler(root.clone());
+                    match state {
+                        Some(state) => {
+                            log::trace!("UiState");
+                            let root = self.atom_root();
+                            state.handler(root.clone());
 
-                        self.scheduler_tx.start_send(SchedulerMsg::NewTask(ScopeId(0))).unwrap();
+                            self.scheduler_tx.start_send(SchedulerMsg::NewTask(ScopeId(0))).unwrap();
+                        }
+                        None => break,
                     }
                 }
+                // 3) when window is closed
+                _ = &mut self.shutdown_rx => {
+                    log::trace!("VirtualDom shutdown");
+                    break;
+                }
             }
         }
     }
@@ -126,3 +144,208 @@ where
         ui_context.rerender();
     }
 }
+
+/// Channels to talk to VirtualDom running on its own thread. VirtualDom stops when this handle is
+/// dropped.
+pub struct VirtualDomHandle {
+    pub scheduler_tx: futures_mpsc::UnboundedSender<SchedulerMsg>,
+    pub edit_queue: Arc<Mutex<Vec<String>>>,
+    _shutdown_tx: oneshot::Sender<()>,
+}
+
+type SpawnFn<UiAction, AsyncAction> = Box<
+    dyn Fn(
+            WindowId,
+            UiEventProxy<UiAction, AsyncAction>,
+            Option<Box<dyn Any + Send + Sync>>,
+        ) -> VirtualDomHandle
+        + Send
+        + Sync,
+>;
+
+/// Spawns VirtualDom for each window. Holds default root component and additional named roots.
+pub struct VirtualDomSpawner<UiAction: Debug + 'static, AsyncAction: 'static> {
+    root: SpawnFn<UiAction, AsyncAction>,
+    roots: HashMap<String, SpawnFn<UiAction, AsyncAction>>,
+    runtime: AsyncRuntime,
+}
+
+impl<UiAction, AsyncAction> VirtualDomSpawner<UiAction, AsyncAction>
+where
+    UiAction: 'static + Send + Sync + Clone + Debug,
+    AsyncAction: 'static + Send + Sync + Clone + Debug,
+{
+    pub fn new<UiState, RootProps>(
+        runtime: AsyncRuntime,
+        Root: Component<RootProps>,
+        root_props: RootProps,
+        ui_state: UiStateBroadcast<UiState>,
+        ui_action_tx: mpsc::Sender<UiAction>,
+    ) -> Self
+    where
+        UiState: 'static + Send + Sync + Clone + UiStateHandler,
+        RootProps: 'static + Send + Sync + Clone,
+    {
+        Self {
+            root: Self::spawn_fn(runtime.clone(), Root, root_props, ui_state, ui_action_tx),
+            roots: HashMap::default(),
+            runtime,
+        }
+    }
+
+    pub fn add_root<UiState, RootProps>(
+        &mut self,
+        name: String,
+        Root: Component<RootProps>,
+        root_props: RootProps,
+        ui_state: UiStateBroadcast<UiState>,
+        ui_action_tx: mpsc::Sender<UiAction>,
+    ) where
+        UiState: 'static + Send + Sync + Clone + UiStateHandler,
+        RootProps: 'static + Send + Sync + Clone,
+    {
+        self.roots.insert(
+            name,
+            Self::spawn_fn(
+                self.runtime.clone(),
+                Root,
+                root_props,
+                ui_state,
+                ui_action_tx,
+            ),
+        );
+    }
+
+    /// Spawn VirtualDom with default root component
+    pub fn spawn(
+        &self,
+        window_id: WindowId,
+        proxy: UiEventProxy<UiAction, AsyncAction>,
+    ) -> VirtualDomHandle {
+        (self.root)(window_id, proxy, None)
+    }
+
+    /// Spawn VirtualDom with named root component. Returns `None` if root is not registered.
+    pub fn spawn_root(
+        &self,
+        name: &str,
+        window_id: WindowId,
+        proxy: UiEventProxy<UiAction, AsyncAction>,
+        props: Option<Box<dyn Any + Send + Sync>>,
+    ) -> Option<VirtualDomHandle> {
+        self.roots
+            .get(name)
+            .map(|spawn| spawn(window_id, proxy, props))
+    }
+
+    fn spawn_fn<UiState, RootProps>(
+        runtime: AsyncRuntime,
+        Root: Component<RootProps>,
+        root_props: RootProps,
+        ui_state: UiStateBroadcast<UiState>,
+        ui_action_tx: mpsc::Sender<UiAction>,
+    ) -> SpawnFn<UiAction, AsyncAction>
+    where
+        UiState: 'static + Send + Sync + Clone + UiStateHandler,
+        RootProps: 'static + Send + Sync + Clone,
+    {
+        Box::new(move |window_id, proxy, props| {
+            let (scheduler_tx, scheduler_rx) = futures_mpsc::unbounded::<SchedulerMsg>();
+            let (shutdown_tx, shutdown_rx) = oneshot::channel();
+            let edit_queue = Arc::new(Mutex::new(Vec::new()));
+            let ui_state_rx = ui_state.subscribe();
+            let ui_context = UiContext::new(window_id, proxy, ui_action_tx.clone());
+
+            let root_props = match props {
+                Some(props) => match props.downcast::<RootProps>() {
+                    Ok(props) => *props,
+                    Err(_) => {
+                        log::error!("Root props type mismatch: fall back to default props");
+                        root_props.clone()
+                    }
+                },
+                None => root_props.clone(),
+            };
+            let edit_queue_clone = edit_queue.clone();
+            let scheduler_tx_clone = scheduler_tx.clone();
+            let runtime = runtime.clone();
+            // VirtualDom is not Send, so it is driven by a thread outside of the runtime
+            std::thread::spawn(move || {
+                runtime.handle().block_on(async move {
+                    let mut virtual_dom = VirtualDom::<UiState, UiAction, AsyncAction>::new(
+                        Root,
+                        root_props,
+                        edit_queue_clone,
+                        (scheduler_tx_clone, scheduler_rx),
+                        ui_state_rx,
+                        shutdown_rx,
+                    );
+                    virtual_dom.provide_ui_context(ui_context);
+
+                    virtual_dom.run().await;
+                });
+            });
+
+            VirtualDomHandle {
+                scheduler_tx,
+                edit_queue,
+                _shutdown_tx: shutdown_tx,
+            }
+        })
+    }
+}
+
+/// Fan out UiState to every VirtualDom. Keeps latest value for each variant so that VirtualDom
+/// created later starts from current state.
+pub struct UiStateBroadcast<UiState> {
+    inner: Arc<Mutex<UiStateBroadcastInner<UiState>>>,
+}
+
+struct UiStateBroadcastInner<UiState> {
+    senders: Vec<mpsc::UnboundedSender<UiState>>,
+    latest: HashMap<Discriminant<UiState>, UiState>,
+}
+
+impl<UiState> Clone for UiStateBroadcast<UiState> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<UiState> UiStateBroadcast<UiState>
+where
+    UiState: 'static + Send + Clone,
+{
+    pub fn new(runtime: &AsyncRuntime, mut ui_state_rx: mpsc::Receiver<UiState>) -> Self {
+        let broadcast = Self {
+            inner: Arc::new(Mutex::new(UiStateBroadcastInner {
+                senders: Vec::new(),
+                latest: HashMap::default(),
+            })),
+        };
+
+        let inner = broadcast.inner.clone();
+        runtime.spawn(async move {
+            while let Some(state) = ui_state_rx.recv().await {
+                let mut inner = inner.lock().unwrap();
+                inner.latest.insert(discriminant(&state), state.clone());
+                inner.senders.retain(|tx| tx.send(state.clone()).is_ok());
+            }
+        });
+
+        broadcast
+    }
+
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<UiState> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut inner = self.inner.lock().unwrap();
+        for state in inner.latest.values() {
+            let _ = tx.send(state.clone());
+        }
+        inner.senders.push(tx);
+
+        rx
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
-    context::ProxyType,
-    event::{trigger_from_serialized, IpcMessage, KeyboardEvent, UiEvent, WindowEvent},
+    context::UiEventProxy,
+    event::{handle_ipc_message, UiEvent},
+    ipc::{IpcHandlers, IPC_BRIDGE_JS},
     protocol,
     setting::DesktopSettings,
+    virtual_dom::{VirtualDomHandle, VirtualDomSpawner},
 };
 use bevy::{
     ecs::world::WorldCell,
@@ -14,6 +16,7 @@ use dioxus_core::SchedulerMsg;
 use futures_channel::mpsc;
 use raw_window_handle::HasRawWindowHandle;
 use std::{
+    any::Any,
     fmt::{self, Debug},
     marker::PhantomData,
     sync::{atomic::AtomicBool, Arc, Mutex},
@@ -73,6 +76,7 @@ impl DioxusWindows {
 
     pub fn remove(&mut self, id: WindowId) -> Option<Window> {
         let tao_window_id = self.window_id_to_tao.remove(&id)?;
+        self.tao_to_window_id.remove(&tao_window_id);
         self.windows.remove(&tao_window_id)
     }
 
@@ -82,6 +86,28 @@ impl DioxusWindows {
         window_id: WindowId,
         window_descriptor: &WindowDescriptor,
     ) -> BevyWindow
+    where
+        UiAction: 'static + Send + Sync + Clone + Debug,
+        AsyncAction: 'static + Send + Sync + Clone + Debug,
+        RootProps: 'static + Send + Sync + Clone,
+    {
+        self.create_with_root::<UiAction, AsyncAction, RootProps>(
+            world,
+            window_id,
+            window_descriptor,
+            None,
+        )
+        .unwrap()
+    }
+
+    /// Create window rendering named root component. Default root is used when `root` is `None`.
+    pub fn create_with_root<UiAction, AsyncAction, RootProps>(
+        &mut self,
+        world: &WorldCell,
+        window_id: WindowId,
+        window_descriptor: &WindowDescriptor,
+        root: Option<(&str, Option<Box<dyn Any + Send + Sync>>)>,
+    ) -> Option<BevyWindow>
     where
         UiAction: 'static + Send + Sync + Clone + Debug,
         AsyncAction: 'static + Send + Sync + Clone + Debug,
@@ -91,13 +117,21 @@ impl DioxusWindows {
             .get_non_send_resource_mut::<EventLoop<UiEvent<UiAction, AsyncAction>>>()
             .unwrap();
         let proxy = event_loop.create_proxy();
-        let dom_tx = world
-            .get_resource::<mpsc::UnboundedSender<SchedulerMsg>>()
+        let spawner = world
+            .get_resource::<VirtualDomSpawner<UiAction, AsyncAction>>()
             .unwrap();
-        let edit_queue = world
-            .get_resource::<Arc<Mutex<Vec<String>>>>()
-            .unwrap()
-            .clone();
+        let virtual_dom = match root {
+            Some((name, props)) => {
+                match spawner.spawn_root(name, window_id, proxy.clone().into(), props) {
+                    Some(virtual_dom) => virtual_dom,
+                    None => {
+                        log::error!("Root component is not registered: {name}");
+                        return None;
+                    }
+                }
+            }
+            None => spawner.spawn(window_id, proxy.clone().into()),
+        };
 
         let tao_window =
             Self::create_tao_window::<UiAction, AsyncAction>(&event_loop, &window_descriptor);
@@ -106,20 +140,19 @@ impl DioxusWindows {
         let bevy_window = Self::create_bevy_window(window_id, &tao_window, &window_descriptor);
         let (webview, is_ready) = Self::create_webview::<UiAction, AsyncAction, RootProps>(
             world,
+            window_id,
             window_descriptor,
             tao_window,
-            proxy,
-            dom_tx.clone(),
+            proxy.into(),
+            virtual_dom.scheduler_tx.clone(),
         );
 
-        self.windows.insert(
-            tao_window_id,
-            Window::new(webview, dom_tx.clone(), is_ready, edit_queue),
-        );
+        self.windows
+            .insert(tao_window_id, Window::new(webview, is_ready, virtual_dom));
         self.window_id_to_tao.insert(window_id, tao_window_id);
         self.tao_to_window_id.insert(tao_window_id, window_id);
 
-        bevy_window
+        Some(bevy_window)
     }
 
     pub fn get_fitting_videomode(monitor: &MonitorHandle, width: u32, height: u32) -> VideoMode {
@@ -315,9 +348,10 @@ impl DioxusWindows {
 
     fn create_webview<UiAction, AsyncAction, RootProps>(
         world: &WorldCell,
+        window_id: WindowId,
         window_descriptor: &WindowDescriptor,
         tao_window: TaoWindow,
-        proxy: ProxyType<UiAction, AsyncAction>,
+        proxy: UiEventProxy<UiAction, AsyncAction>,
         dom_tx: mpsc::UnboundedSender<SchedulerMsg>,
     ) -> (WebView, Arc<AtomicBool>)
     where
@@ -335,6 +369,7 @@ impl DioxusWindows {
         let resource_dir = settings.resource_dir.clone();
         let index_file = settings.custom_index.clone();
         let is_ready_clone = is_ready.clone();
+        let ipc_handlers = world.resource::<IpcHandlers>().clone();
 
         let mut webview = WebViewBuilder::new(tao_window)
             .unwrap()
@@ -342,42 +377,16 @@ impl DioxusWindows {
             .with_url("dioxus://index.html/")
             .unwrap()
             .with_ipc_handler(move |_window: &TaoWindow, payload: String| {
-                IpcMessage::from_payload(&payload)
-                    .map(|message| match message.method() {
-                        "user_event" => {
-                            let event = trigger_from_serialized(message.params());
-                            log::trace!("IpcMessage user_event: {event:?}");
-                            dom_tx.unbounded_send(SchedulerMsg::Event(event)).unwrap();
-                        }
-                        "keyboard_event" => {
-                            log::trace!("IpcMessage: keyboard_event");
-                            let event = KeyboardEvent::from_value(message.params());
-                            proxy.send_event(UiEvent::KeyboardEvent(event)).unwrap();
-                        }
-                        "initialize" => {
-                            log::trace!("IpcMessage: initialize");
-                            is_ready_clone.store(true, std::sync::atomic::Ordering::Relaxed);
-                            let _ = proxy.send_event(UiEvent::WindowEvent(WindowEvent::Rerender));
-                        }
-                        "browser_open" => {
-                            log::trace!("IpcMessage: browser_open");
-                            let data = message.params();
-                            log::trace!("Open browser: {:?}", data);
-                            if let Some(temp) = data.as_object() {
-                                if temp.contains_key("href") {
-                                    let url = temp.get("href").unwrap().as_str().unwrap();
-                                    if let Err(e) = webbrowser::open(url) {
-                                        log::error!("Open Browser error: {:?}", e);
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    })
-                    .unwrap_or_else(|| {
-                        log::warn!("invalid IPC message received");
-                    })
+                handle_ipc_message(
+                    window_id,
+                    &payload,
+                    &dom_tx,
+                    &proxy,
+                    &is_ready_clone,
+                    &ipc_handlers,
+                )
             })
+            .with_initialization_script(IPC_BRIDGE_JS)
             .with_custom_protocol(String::from("dioxus"), move |r| {
                 protocol::handler(
                     r,
@@ -476,20 +485,17 @@ pub struct Window {
     pub dom_tx: mpsc::UnboundedSender<SchedulerMsg>,
     is_ready: Arc<AtomicBool>,
     edit_queue: Arc<Mutex<Vec<String>>>,
+    _virtual_dom: VirtualDomHandle,
 }
 
 impl Window {
-    fn new(
-        webview: WebView,
-        dom_tx: mpsc::UnboundedSender<SchedulerMsg>,
-        is_ready: Arc<AtomicBool>,
-        edit_queue: Arc<Mutex<Vec<String>>>,
-    ) -> Self {
+    fn new(webview: WebView, is_ready: Arc<AtomicBool>, virtual_dom: VirtualDomHandle) -> Self {
         Self {
             webview,
-            dom_tx,
+            dom_tx: virtual_dom.scheduler_tx.clone(),
             is_ready,
-            edit_queue,
+            edit_queue: virtual_dom.edit_queue.clone(),
+            _virtual_dom: virtual_dom,
         }
     }
 
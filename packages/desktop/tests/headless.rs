@@ -0,0 +1,155 @@
+use dip::{
+    bevy::{ecs::event::Events, utils::Duration, window::WindowId},
+    prelude::*,
+};
+
+#[test]
+fn click_updates_state_and_rerenders() {
+    let mut app = App::new();
+    app.add_plugin(HeadlessDesktopPlugin::<UiState, UiAction, NoAsyncAction>::new(Root))
+        .add_plugin(UiStatePlugin)
+        .add_plugin(UiActionPlugin)
+        .add_system(handle_increment);
+
+    let window = app.world.resource::<HeadlessWindow<UiAction>>().clone();
+
+    assert!(window.update_until_rendered(&mut app, Duration::from_secs(1)));
+    let edits = window.take_edits();
+    assert!(edits.iter().any(|edit| edit.contains("count: 0")));
+
+    window.send_user_event(serde_json::json!({
+        "event": "click",
+        "mounted_dom_id": click_listener_id(&edits),
+        "contents": {
+            "alt_key": false,
+            "button": 0,
+            "buttons": 1,
+            "client_x": 0,
+            "client_y": 0,
+            "ctrl_key": false,
+            "meta_key": false,
+            "offset_x": 0,
+            "offset_y": 0,
+            "page_x": 0,
+            "page_y": 0,
+            "screen_x": 0,
+            "screen_y": 0,
+            "shift_key": false,
+        },
+    }));
+
+    assert!(window.update_until_rendered(&mut app, Duration::from_secs(1)));
+    assert_eq!(app.world.resource::<Count>().value, 1);
+    assert!(window
+        .take_edits()
+        .iter()
+        .any(|edit| edit.contains("count: 1")));
+}
+
+#[test]
+fn eval_request_is_answered_through_ipc() {
+    let mut app = App::new();
+    app.add_plugin(HeadlessDesktopPlugin::<NoUiState, NoUiAction, NoAsyncAction>::new(Hello));
+
+    let window = app.world.resource::<HeadlessWindow>().clone();
+    let request = EvalRequest::new(WindowId::primary(), "return document.title");
+    app.world
+        .resource_mut::<Events<EvalRequest>>()
+        .send(request.clone());
+    app.update();
+
+    let scripts = window.take_scripts();
+    assert_eq!(scripts.len(), 1);
+    assert!(scripts[0].contains("return document.title"));
+
+    let mut responses = app.world.resource::<Events<EvalResponse>>().get_reader();
+    window.send_ipc(
+        &serde_json::json!({
+            "method": "eval_result",
+            "params": { "id": request.id, "value": "dip" },
+        })
+        .to_string(),
+    );
+    app.update();
+
+    let responses = responses
+        .iter(app.world.resource::<Events<EvalResponse>>())
+        .map(|response| (response.id, response.result.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(responses, vec![(request.id, Ok(serde_json::json!("dip")))]);
+}
+
+#[test]
+fn ipc_handler_reply_is_recorded() {
+    let mut app = App::new();
+    app.add_plugin(
+        HeadlessDesktopPlugin::<NoUiState, NoUiAction, NoAsyncAction>::new(Hello)
+            .ipc_handler("greet", |name: String| {
+                Ok::<_, String>(format!("Hello, {name}"))
+            }),
+    );
+
+    let window = app.world.resource::<HeadlessWindow>().clone();
+    window.send_ipc(r#"{ "method": "greet", "params": "dip", "id": 7 }"#);
+    app.update();
+
+    assert_eq!(
+        window.take_scripts(),
+        vec![r#"window.dip.__reply(7, true, "Hello, dip")"#.to_string()]
+    );
+}
+
+// Element id which onclick listener is attached to
+fn click_listener_id(edits: &[String]) -> u64 {
+    edits
+        .iter()
+        .flat_map(|edit| serde_json::from_str::<Vec<serde_json::Value>>(edit).unwrap())
+        .find(|edit| edit["type"] == "NewEventListener" && edit["event_name"] == "click")
+        .and_then(|edit| edit["root"].as_u64())
+        .expect("onclick listener is rendered")
+}
+
+#[allow(non_snake_case)]
+fn Root(cx: Scope) -> Element {
+    let count = use_read(&cx, COUNT);
+    let window = use_window::<UiAction, NoAsyncAction>(&cx);
+
+    cx.render(rsx! {
+        p { "count: {count.value}" }
+        button {
+            onclick: move |_| window.send(UiAction::increment()),
+            "+",
+        }
+    })
+}
+
+#[allow(non_snake_case)]
+fn Hello(cx: Scope) -> Element {
+    cx.render(rsx! { h1 { "Hello, World !" } })
+}
+
+#[ui_state]
+struct UiState {
+    count: Count,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Count {
+    value: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Increment;
+
+#[ui_action]
+impl ActionCreator {
+    fn increment() -> Increment {
+        Increment
+    }
+}
+
+fn handle_increment(mut events: EventReader<Increment>, mut count: ResMut<Count>) {
+    for _ in events.iter() {
+        count.value += 1;
+    }
+}
@@ -3,7 +3,10 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use std::{collections::HashMap, fmt, str::FromStr};
-use syn::{FnArg, ImplItem, ImplItemMethod, ItemImpl, PathArguments, ReturnType, Type};
+use syn::{
+    FnArg, GenericArgument, ImplItem, ImplItemMethod, ItemImpl, PathArguments, ReturnType, Type,
+    TypeParamBound,
+};
 
 pub struct ActionParser {
     action_type: ActionType,
@@ -39,46 +42,44 @@ impl ActionParser {
         };
 
         for item in self.action_creator_impl.clone().items {
-            match item {
-                ImplItem::Method(m) => {
-                    let method_name_raw = &m.sig.ident;
-                    let method_name = quote! { #method_name_raw };
-                    let (arg_keys, args) = Self::method_args(&m);
-                    let (async_key, await_key) = if m.sig.asyncness.is_some() {
-                        (quote! { async }, quote! { .await })
-                    } else {
-                        (quote! {}, quote! {})
-                    };
-
-                    let (action_name, action_ty) = Self::action_name_and_ty(&m);
-                    self.actions
-                        .insert(action_name.to_string(), quote! { #action_ty });
-
-                    tokens.action_methods.push(self.action_method(
-                        &method_name,
-                        &quote! { #action_name },
-                        args,
-                        arg_keys,
-                        &async_key,
-                        &await_key,
-                    ));
-                }
-                _ => {}
+            if let ImplItem::Method(m) = item {
+                let method_name_raw = &m.sig.ident;
+                let method_name = quote! { #method_name_raw };
+                let (arg_keys, args) = Self::method_args(&m);
+                let (async_key, await_key) = if m.sig.asyncness.is_some() {
+                    (quote! { async }, quote! { .await })
+                } else {
+                    (quote! {}, quote! {})
+                };
+
+                let (action_name, action_ty) = Self::action_name_and_ty(&m);
+                self.actions
+                    .insert(action_name.to_string(), quote! { #action_ty });
+
+                tokens.action_methods.push(self.action_method(
+                    &method_name,
+                    &quote! { #action_name },
+                    args,
+                    arg_keys,
+                    &async_key,
+                    &await_key,
+                    Self::is_stream(&m),
+                ));
             }
         }
 
         for (action_name_str, action_ty) in self.actions.iter() {
-            let action_name = TokenStream2::from_str(&action_name_str).unwrap();
+            let action_name = TokenStream2::from_str(action_name_str).unwrap();
             let action_snake =
                 TokenStream2::from_str(&action_name_str.to_case(Case::Snake)).unwrap();
 
             tokens
                 .enum_variants
-                .push(Self::enum_variant(&action_name, &action_ty));
-            tokens.add_events.push(Self::add_event(&action_ty));
+                .push(Self::enum_variant(&action_name, action_ty));
+            tokens.add_events.push(Self::add_event(action_ty));
             tokens
                 .handler_args
-                .push(Self::handler_arg(&action_ty, &action_snake));
+                .push(Self::handler_arg(action_ty, &action_snake));
             tokens
                 .handlers
                 .push(self.handler(&action_name, &action_snake));
@@ -96,7 +97,7 @@ impl ActionParser {
     }
 
     fn action_creator_name(&self) -> TokenStream2 {
-        let name = match &**&self.action_creator_impl.self_ty {
+        let name = match &*self.action_creator_impl.self_ty {
             Type::Path(p) => {
                 let name = &p.path.segments[0].ident;
                 quote! { #name }
@@ -121,6 +122,12 @@ impl ActionParser {
     // pub fn create_todo(title: &String) -> Self {
     //     Self::CreateTodo(ActionCreator::create_todo(title))
     // }
+    //
+    // example (stream)
+    // pub fn download(url: String) -> impl Stream<Item = Self> {
+    //     ActionCreator::download(url).map(Self::Progress)
+    // }
+    #[allow(clippy::too_many_arguments)]
     fn action_method(
         &self,
         method_name: &TokenStream2,
@@ -129,9 +136,19 @@ impl ActionParser {
         arg_keys: Vec<TokenStream2>,
         async_key: &TokenStream2,
         await_key: &TokenStream2,
+        is_stream: bool,
     ) -> TokenStream2 {
         let action_creator_name = self.action_creator_name();
 
+        if is_stream {
+            return quote! {
+                pub #async_key fn #method_name(#(#args)*) -> impl ::dip::core::task::Stream<Item = Self> {
+                    use ::dip::core::task::StreamExt;
+                    #action_creator_name::#method_name(#(#arg_keys)*)#await_key.map(Self::#action)
+                }
+            };
+        }
+
         quote! {
             pub #async_key fn #method_name(#(#args)*) -> Self {
                 Self::#action(#action_creator_name::#method_name(#(#arg_keys)*)#await_key)
@@ -176,27 +193,34 @@ impl ActionParser {
 
     fn action_name_and_ty(method: &ImplItemMethod) -> (TokenStream2, TokenStream2) {
         match &method.sig.output {
-            ReturnType::Type(_, return_type) => match *return_type.clone() {
-                Type::Path(type_path) => match type_path.path.get_ident() {
-                    Some(p) => (quote! { #p }, quote! { #p }),
-                    None => match type_path.path.segments.first() {
-                        Some(s) => match &s.arguments {
-                            PathArguments::AngleBracketed(g) => {
-                                let arg = g.args.first().unwrap();
-                                (quote! { #arg }, quote! { #type_path })
-                            }
-                            _ => {
-                                panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
-                            }
-                        },
-                        None => {
+            ReturnType::Type(_, return_type) => match Self::stream_item(return_type) {
+                Some(item) => Self::action_name_and_ty_from_type(item),
+                None => Self::action_name_and_ty_from_type(return_type),
+            },
+            _ => {
+                panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
+            }
+        }
+    }
+
+    fn action_name_and_ty_from_type(ty: &Type) -> (TokenStream2, TokenStream2) {
+        match ty {
+            Type::Path(type_path) => match type_path.path.get_ident() {
+                Some(p) => (quote! { #p }, quote! { #p }),
+                None => match type_path.path.segments.first() {
+                    Some(s) => match &s.arguments {
+                        PathArguments::AngleBracketed(g) => {
+                            let arg = g.args.first().unwrap();
+                            (quote! { #arg }, quote! { #type_path })
+                        }
+                        _ => {
                             panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
                         }
                     },
+                    None => {
+                        panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
+                    }
                 },
-                _ => {
-                    panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
-                }
             },
             _ => {
                 panic!("Cannot find event name. Make sure to sepcify return event in action creator methods.");
@@ -204,16 +228,45 @@ impl ActionParser {
         }
     }
 
+    // example: impl Stream<Item = Progress> -> Some(Progress)
+    fn stream_item(ty: &Type) -> Option<&Type> {
+        match ty {
+            Type::ImplTrait(impl_trait) => impl_trait.bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(t) => {
+                    let segment = t.path.segments.last()?;
+                    if segment.ident != "Stream" {
+                        return None;
+                    }
+                    match &segment.arguments {
+                        PathArguments::AngleBracketed(g) => {
+                            g.args.iter().find_map(|arg| match arg {
+                                GenericArgument::Binding(b) if b.ident == "Item" => Some(&b.ty),
+                                _ => None,
+                            })
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn is_stream(method: &ImplItemMethod) -> bool {
+        match &method.sig.output {
+            ReturnType::Type(_, return_type) => Self::stream_item(return_type).is_some(),
+            _ => false,
+        }
+    }
+
     fn method_args(method: &ImplItemMethod) -> (Vec<TokenStream2>, Vec<TokenStream2>) {
         let mut arg_keys = vec![];
         let mut args = vec![];
         for arg in method.sig.inputs.iter() {
-            match arg {
-                FnArg::Typed(pt) => {
-                    let ident = &pt.pat;
-                    arg_keys.push(quote! { #ident, });
-                }
-                _ => {}
+            if let FnArg::Typed(pt) = arg {
+                let ident = &pt.pat;
+                arg_keys.push(quote! { #ident, });
             }
             args.push(quote! { #arg, });
         }
@@ -1,8 +1,11 @@
+use crate::subcommand::{handler_path, has_clap_ident};
 use proc_macro::TokenStream;
-use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use std::str::FromStr;
-use syn::ItemStruct;
+use syn::{
+    Attribute, GenericArgument, ItemStruct, Lit, LitStr, Meta, MetaNameValue, NestedMeta,
+    PathArguments, Type, TypePath,
+};
 
 pub struct CliParser {
     cli_struct: ItemStruct,
@@ -21,57 +24,135 @@ impl CliParser {
             ..Default::default()
         };
 
+        let mut global_args = vec![];
         for f in self.cli_struct.fields.iter() {
-            for a in f.attrs.iter() {
-                for t in a.tokens.clone().into_iter() {
-                    match t {
-                        TokenTree::Group(g) => {
-                            for s in g.stream() {
-                                match s {
-                                    TokenTree::Ident(ident) => {
-                                        if ident.to_string() == "subcommand" {
-                                            let subcommand_name = f.ident.as_ref().unwrap();
-                                            let ty = &f.ty;
-                                            token.add_event = quote! { .add_event::<#ty>() };
-                                            token.insert_subcommand_resource = quote! {
-                                                .insert_resource(cli.#subcommand_name.clone())
-                                            };
-                                            let subcommand_handler_name = TokenStream2::from_str(
-                                                &format!("handle_{}", subcommand_name),
-                                            )
-                                            .unwrap();
-                                            token.add_subcommand_handler = quote! {
-                                                .add_startup_system_to_stage(
-                                                    ::dip::core::schedule::DipStartupStage::Action,
-                                                    convert_subcommand_to_event.before(
-                                                        #subcommand_handler_name
-                                                    )
-                                                )
-                                            };
-                                            token.subcommand_handler = quote! {
-                                                fn convert_subcommand_to_event(
-                                                    subcommand: ::dip::bevy::ecs::system::Res<#ty>,
-                                                    mut #subcommand_name: ::dip::bevy::ecs::event::EventWriter<#ty>,
-                                                ) {
-                                                    #subcommand_name.send(subcommand.clone());
-                                                }
-                                            };
-                                        }
-                                    }
-                                    _ => {}
-                                }
+            let name = f.ident.as_ref().unwrap();
+
+            if let Some(attr) = config_attr(&f.attrs) {
+                // Defaults and unset flags must not override config files and env
+                let id = name.to_string();
+                let config_override = match attr {
+                    ConfigAttr::Key(key) if option_inner_ty(&f.ty).is_some() => quote! {
+                        if matches.value_source(#id) == Some(::clap::ValueSource::CommandLine) {
+                            if let Some(value) = &cli.#name {
+                                overrides.set(#key, value.to_string());
                             }
                         }
-                        _ => {}
+                    },
+                    ConfigAttr::Key(key) => quote! {
+                        if matches.value_source(#id) == Some(::clap::ValueSource::CommandLine) {
+                            overrides.set(#key, cli.#name.to_string());
+                        }
+                    },
+                    ConfigAttr::Overrides => quote! {
+                        for pair in cli.#name.iter() {
+                            overrides.set_pair(pair).map_err(|e| {
+                                ::clap::Error::raw(::clap::ErrorKind::InvalidValue, format!("{e}\n"))
+                            })?;
+                        }
+                    },
+                };
+                token.config_overrides.push(config_override);
+            }
+
+            if !has_clap_ident(&f.attrs, "subcommand") {
+                let ty = &f.ty;
+                global_args.push(quote! { pub #name: #ty });
+                token
+                    .global_args_fields
+                    .push(quote! { #name: cli.#name.clone() });
+                continue;
+            }
+
+            // `Option<Action>` falls back to `Action::default()` when subcommand is omitted
+            let (ty, subcommand) = match option_inner_ty(&f.ty) {
+                Some(ty) => (ty, quote! { cli.#name.clone().unwrap_or_default() }),
+                None => (&f.ty, quote! { cli.#name.clone() }),
+            };
+            token.add_event = quote! { .add_event::<#ty>() };
+            token.insert_subcommand_resource = quote! {
+                world.insert_resource(#subcommand);
+            };
+            if let Some(handler) = handler_path(ty) {
+                token.add_subcommand_handler = quote! {
+                    .add_system_to_stage(
+                        ::dip::core::schedule::DipStage::Action,
+                        convert_subcommand_to_event.before(#handler)
+                    )
+                };
+            }
+            token.subcommand_handler = quote! {
+                // Subcommand resource is replaced by each REPL command
+                fn convert_subcommand_to_event(
+                    subcommand: Option<::dip::bevy::ecs::system::Res<#ty>>,
+                    mut #name: ::dip::bevy::ecs::event::EventWriter<#ty>,
+                ) {
+                    if let Some(subcommand) = subcommand {
+                        if subcommand.is_changed() {
+                            #name.send(subcommand.clone());
+                        }
                     }
                 }
-            }
+            };
         }
 
+        token.global_args = quote! {
+            /// Arguments of the CLI except subcommand
+            #[allow(dead_code)]
+            #[derive(Clone, Debug)]
+            pub struct GlobalArgs {
+                #(#global_args),*
+            }
+        };
+
         token
     }
 }
 
+enum ConfigAttr {
+    Key(LitStr),
+    Overrides,
+}
+
+// `#[config(key = "server.port")]` or `#[config(overrides)]`
+fn config_attr(attrs: &[Attribute]) -> Option<ConfigAttr> {
+    let attr = attrs.iter().find(|a| a.path.is_ident("config"))?;
+    let nested = match attr.parse_meta() {
+        Ok(Meta::List(list)) => list.nested,
+        _ => panic!("expected #[config(key = \"...\")] or #[config(overrides)]"),
+    };
+
+    nested.iter().find_map(|meta| match meta {
+        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+            path,
+            lit: Lit::Str(key),
+            ..
+        })) if path.is_ident("key") => Some(ConfigAttr::Key(key.clone())),
+        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("overrides") => {
+            Some(ConfigAttr::Overrides)
+        }
+        _ => None,
+    })
+}
+
+// `Option<T>` -> `T`
+fn option_inner_ty(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(TypePath { path, .. }) => path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 #[derive(Default)]
 pub struct CliToken {
     cli_name: TokenStream2,
@@ -79,6 +160,9 @@ pub struct CliToken {
     add_event: TokenStream2,
     add_subcommand_handler: TokenStream2,
     subcommand_handler: TokenStream2,
+    global_args: TokenStream2,
+    global_args_fields: Vec<TokenStream2>,
+    config_overrides: Vec<TokenStream2>,
 }
 
 impl CliToken {
@@ -89,12 +173,19 @@ impl CliToken {
             add_event,
             add_subcommand_handler,
             subcommand_handler,
+            global_args,
+            global_args_fields,
+            config_overrides,
         } = self;
 
         let gen = quote! {
             pub struct CliPlugin<AsyncAction> {
                 async_action_type: std::marker::PhantomData<AsyncAction>,
                 continuous: bool,
+                repl: bool,
+                tick_rate: Option<::dip::bevy::utils::Duration>,
+                prompt: Option<String>,
+                history: Option<std::path::PathBuf>,
             }
 
             impl<AsyncAction> CliPlugin<AsyncAction> {
@@ -102,6 +193,10 @@ impl CliToken {
                     Self {
                         async_action_type: std::marker::PhantomData,
                         continuous: false,
+                        repl: false,
+                        tick_rate: None,
+                        prompt: None,
+                        history: None,
                     }
                 }
 
@@ -109,8 +204,45 @@ impl CliToken {
                     Self {
                         async_action_type: std::marker::PhantomData,
                         continuous: true,
+                        repl: false,
+                        tick_rate: None,
+                        prompt: None,
+                        history: None,
                     }
                 }
+
+                /// Read subcommands from stdin instead of process arguments and dispatch each of
+                /// them until `exit` is typed. Supports history and tab completion. `completions
+                /// <SHELL>` and `man` print to stdout like they do in other modes.
+                pub fn repl() -> Self {
+                    Self {
+                        async_action_type: std::marker::PhantomData,
+                        continuous: true,
+                        repl: true,
+                        tick_rate: None,
+                        prompt: None,
+                        history: None,
+                    }
+                }
+
+                /// Update app at fixed rate in addition to incoming async actions. Only applies to
+                /// continuous and REPL mode.
+                pub fn with_tick_rate(mut self, tick_rate: ::dip::bevy::utils::Duration) -> Self {
+                    self.tick_rate = Some(tick_rate);
+                    self
+                }
+
+                /// Change REPL prompt. Defaults to `"{bin_name}> "`. Only applies to REPL mode.
+                pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+                    self.prompt = Some(prompt.into());
+                    self
+                }
+
+                /// Persist REPL history to file across sessions. Only applies to REPL mode.
+                pub fn with_history(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+                    self.history = Some(path.into());
+                    self
+                }
             }
 
             impl<AsyncAction> ::dip::bevy::app::Plugin for CliPlugin<AsyncAction>
@@ -118,53 +250,82 @@ impl CliToken {
                 AsyncAction: 'static + Send + Sync + Clone,
             {
                 fn build(&self, app: &mut ::dip::bevy::app::App) {
-                    use ::clap::Parser;
                     use ::dip::bevy::ecs::{
                         schedule::ParallelSystemDescriptorCoercion,
                         system::IntoSystem,
                     };
 
-                    let cli = #cli_name::parse();
                     let continuous = self.continuous;
+                    let repl = self.repl;
+                    let tick_rate = self.tick_rate;
+                    let prompt = self.prompt.clone();
+                    let history = self.history.clone();
+
+                    if !repl {
+                        ::dip::cli::generate::run_generators(
+                            <#cli_name as ::clap::CommandFactory>::command(),
+                            std::env::args(),
+                        );
+                        let matches = <#cli_name as ::clap::CommandFactory>::command().get_matches();
+                        if let Err(e) = insert_cli_resources(&mut app.world, &matches) {
+                            e.exit();
+                        }
+                    }
 
                     app.add_plugin(::dip::core::schedule::UiSchedulePlugin)
-                        #insert_subcommand_resource
-                        .insert_resource(cli)
+                        .add_event::<::dip::core::task::AsyncActionFailed>()
+                        .add_event::<::dip::cli::AppExitCode>()
+                        .init_resource::<::dip::cli::AppExitCode>()
+                        .add_system_to_stage(::dip::bevy::app::CoreStage::Last, ::dip::cli::exit::handle_app_exit_code)
                         #add_event
-                        .set_runner(move |mut app| {
-                            if !continuous {
-                                app.update();
-                            } else {
-                                let (async_action_tx, mut async_action_rx) = ::tokio::sync::mpsc::channel::<AsyncAction>(8);
-                                let async_action = ::dip::core::task::AsyncActionPool::new(async_action_tx.clone());
-                                app.world.insert_resource(async_action);
-
-                                app.update();
-
-                                loop {
-                                    if let Some(app_exit_events) = app.world.get_resource::<::dip::bevy::ecs::event::Events<::dip::bevy::app::AppExit>>() {
-                                        let mut app_exit_event_reader = ::dip::bevy::ecs::event::ManualEventReader::<::dip::bevy::app::AppExit>::default();
-                                        if app_exit_event_reader.iter(app_exit_events).last().is_some() {
-                                            break
-                                        }
-                                    }
-
-                                    while let Ok(action) = async_action_rx.try_recv() {
-                                        let mut events = app
-                                            .world
-                                            .get_resource_mut::<::dip::bevy::ecs::event::Events<AsyncAction>>()
-                                            .expect("Provide AsyncAction event to bevy");
-                                        events.send(action);
-
-                                        app.update();
-                                    }
+                        .set_runner(move |app| {
+                            if repl {
+                                let command = <#cli_name as ::clap::CommandFactory>::command();
+                                let mut repl = ::dip::cli::repl::Repl::new(command.clone(), move |world, args| {
+                                    let matches = command.clone().try_get_matches_from(args)?;
+                                    insert_cli_resources(world, &matches)
+                                });
+                                if let Some(prompt) = &prompt {
+                                    repl = repl.prompt(prompt.clone());
                                 }
-                            };
+                                if let Some(history) = &history {
+                                    repl = repl.history(history.clone());
+                                }
+                                ::dip::cli::runner::run_repl::<AsyncAction>(app, tick_rate, repl);
+                            } else if !continuous {
+                                ::dip::cli::runner::run_oneshot(app);
+                            } else {
+                                ::dip::cli::runner::run_continuous::<AsyncAction>(app, tick_rate);
+                            }
                         })
                         #add_subcommand_handler;
                 }
             }
 
+            // Parse matches into CLI resources. Only values given on command line override
+            // config.
+            fn insert_cli_resources(
+                world: &mut ::dip::bevy::ecs::world::World,
+                matches: &::clap::ArgMatches,
+            ) -> Result<(), ::clap::Error> {
+                let cli = <#cli_name as ::clap::FromArgMatches>::from_arg_matches(matches)?;
+
+                #[allow(unused_mut)]
+                let mut overrides = ::dip::core::config::ConfigOverrides::default();
+                #(#config_overrides)*
+                world.insert_resource(overrides);
+
+                #insert_subcommand_resource
+                world.insert_resource(GlobalArgs {
+                    #(#global_args_fields),*
+                });
+                world.insert_resource(cli);
+
+                Ok(())
+            }
+
+            #global_args
+
             #subcommand_handler
         };
 
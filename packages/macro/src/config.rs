@@ -15,11 +15,9 @@ impl ConfigParser {
     pub fn parse(&self) -> ConfigToken {
         let ident = &self.config_struct.ident;
 
-        let token = ConfigToken {
+        ConfigToken {
             config_name: quote! { #ident },
-        };
-
-        token
+        }
     }
 }
 
@@ -39,6 +37,13 @@ impl ConfigToken {
                 env_separator: &'static str,
                 default_file_str: &'static str,
                 default_file_format: ::config::FileFormat,
+                hot_reload: bool,
+                fallback: Option<fn() -> #config_name>,
+                exit_on_error: bool,
+                app_name: &'static str,
+                profile: Option<&'static str>,
+                profile_env: Option<&'static str>,
+                search_paths: Option<Vec<&'static str>>,
             }
 
             impl ::dip::bevy::app::Plugin for ConfigPlugin {
@@ -46,7 +51,32 @@ impl ConfigToken {
                     use ::dip::bevy::ecs::system::IntoSystem;
 
                     app.insert_resource(#config_name::builder(&self))
+                        .insert_resource(self.files())
+                        .add_event::<::dip::core::config::ConfigReloaded>()
+                        .add_event::<::dip::core::config::ConfigError>()
                         .add_startup_system(build_config);
+
+                    if let Some(fallback) = self.fallback {
+                        app.insert_resource(::dip::core::config::ConfigFallback(fallback));
+                    }
+                    if self.exit_on_error {
+                        app.insert_resource(::dip::core::config::ConfigExitOnError);
+                    }
+
+                    if self.hot_reload {
+                        match ::dip::core::config::ConfigWatcher::new() {
+                            Ok(mut watcher) => {
+                                for name in self.files().names {
+                                    watcher.watch(name);
+                                }
+                                app.insert_resource(watcher).add_system_to_stage(
+                                    ::dip::bevy::app::CoreStage::PreUpdate,
+                                    reload_config,
+                                );
+                            }
+                            Err(e) => ::dip::bevy::log::error!("could not watch config files: {e}"),
+                        }
+                    }
                 }
             }
 
@@ -58,6 +88,13 @@ impl ConfigToken {
                         env_separator: "__",
                         default_file_str: "",
                         default_file_format: ::config::FileFormat::Toml,
+                        hot_reload: false,
+                        fallback: None,
+                        exit_on_error: false,
+                        app_name: env!("CARGO_PKG_NAME"),
+                        profile: None,
+                        profile_env: None,
+                        search_paths: None,
                     }
                 }
             }
@@ -93,15 +130,92 @@ impl ConfigToken {
                     self.default_file_str = default_str;
                     self
                 }
+
+                /// Watch config files and update config resource when they change. Emits
+                /// `ConfigReloaded` or `ConfigError` event.
+                pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+                    self.hot_reload = hot_reload;
+                    self
+                }
+
+                /// Use config returned from `fallback` (e.g. `Config::default`) when it fails to
+                /// load at startup. Without fallback, `ConfigError` is inserted and sent as event,
+                /// then `AppExit` is sent.
+                pub fn fallback(mut self, fallback: fn() -> #config_name) -> Self {
+                    self.fallback = Some(fallback);
+                    self
+                }
+
+                /// Print error report and exit process with code 1 when config fails to load at
+                /// startup and there is no fallback. Meant for CLI apps.
+                pub fn exit_on_error(mut self, exit_on_error: bool) -> Self {
+                    self.exit_on_error = exit_on_error;
+                    self
+                }
+
+                /// Name of config directory and file. Defaults to `CARGO_PKG_NAME`.
+                pub fn app_name(mut self, app_name: &'static str) -> Self {
+                    self.app_name = app_name;
+                    self
+                }
+
+                /// Load `{file}.{profile}` overlay on top of each config file
+                pub fn profile(mut self, profile: &'static str) -> Self {
+                    self.profile = Some(profile);
+                    self
+                }
+
+                /// Env var to choose profile. Defaults to `{APP_NAME}_PROFILE`.
+                pub fn profile_env(mut self, profile_env: &'static str) -> Self {
+                    self.profile_env = Some(profile_env);
+                    self
+                }
+
+                /// Replace platform search paths. Paths are given without extension, lowest
+                /// priority first.
+                pub fn search_paths(mut self, search_paths: Vec<&'static str>) -> Self {
+                    self.search_paths = Some(search_paths);
+                    self
+                }
+
+                /// Resolved search paths
+                pub fn paths(&self) -> ::dip::core::config::ConfigPaths {
+                    let mut paths = ::dip::core::config::ConfigPaths::new(self.app_name);
+                    if let Some(profile_env) = self.profile_env {
+                        paths.profile = std::env::var(profile_env).ok().filter(|p| !p.is_empty());
+                    }
+                    if let Some(profile) = self.profile {
+                        paths.profile = Some(profile.to_string());
+                    }
+                    if let Some(search_paths) = &self.search_paths {
+                        paths.search_paths =
+                            Some(search_paths.iter().map(|p| p.to_string()).collect());
+                    }
+
+                    paths
+                }
+
+                /// Config files in the order of priority, including `CONFIG_PATH`
+                pub fn files(&self) -> ::dip::core::config::ConfigFiles {
+                    let paths = self.paths();
+                    let mut names = vec![];
+                    if self.default_paths {
+                        names.extend(paths.file_names());
+                    }
+                    if let Ok(name) = std::env::var("CONFIG_PATH") {
+                        names.push(name);
+                    }
+
+                    ::dip::core::config::ConfigFiles {
+                        names,
+                        writable: paths.user_files(),
+                        user_file: paths.user_file(),
+                    }
+                }
             }
 
             impl #config_name {
                 pub fn builder(plugin: &ConfigPlugin) -> ::config::builder::ConfigBuilder<::config::builder::DefaultState> {
-                    const PKG_NAME: &str = env!("CARGO_PKG_NAME");
-
-                    let home_dir = dirs::home_dir().unwrap();
-                    let home_dir_str = home_dir.to_str().unwrap();
-
                     let mut builder = ::config::Config::builder();
                     let mut env = ::config::Environment::default().separator(plugin.env_separator);
 
@@ -110,46 +224,17 @@ impl ConfigToken {
                     }
 
                     if plugin.default_paths {
-                        builder = builder
-                            // default config file in binary
-                            .add_source(::config::File::from_str(
-                                plugin.default_file_str,
-                                plugin.default_file_format,
-                            ))
-                            .add_source(
-                                ::config::File::with_name(&format!(
-                                    "{home}/.config/{name}",
-                                    home = &home_dir_str,
-                                    name = PKG_NAME
-                                ))
-                                .required(false),
-                            )
-                            // $HOME/.config/{CARGO_PKG_NAME}/{CARGO_PKG_NAME}
-                            .add_source(
-                                ::config::File::with_name(&format!(
-                                    "{home}/.config/{name}/{name}",
-                                    home = &home_dir_str,
-                                    name = PKG_NAME
-                                ))
-                                .required(false),
-                            )
-                            // $HOME/.{CARGO_PKG_NAME}
-                            .add_source(
-                                ::config::File::with_name(&format!(
-                                    "{home}/.{name}",
-                                    home = &home_dir_str,
-                                    name = PKG_NAME
-                                ))
-                                .required(false),
-                            )
-                            // ./{CARGO_PKG_NAME}
-                            .add_source(
-                                ::config::File::with_name(&format!("{name}", name = PKG_NAME)).required(false),
-                            )
-                            .add_source(env);
+                        // default config file in binary
+                        builder = builder.add_source(::config::File::from_str(
+                            plugin.default_file_str,
+                            plugin.default_file_format,
+                        ));
+                        for name in plugin.paths().file_names() {
+                            builder = builder.add_source(::dip::core::config::ConfigFileSource::new(name));
+                        }
+                        builder = builder.add_source(env);
                     }
 
-
                     if let Ok(name) = std::env::var("CONFIG_PATH") {
                         builder = builder.add_source(
                             ::config::File::with_name(&name)
@@ -158,22 +243,90 @@ impl ConfigToken {
 
                     builder
                 }
+
+                /// Build config from the sources and overrides, then deserialize it
+                pub fn load(
+                    builder: &::config::builder::ConfigBuilder<::config::builder::DefaultState>,
+                    overrides: Option<&::dip::core::config::ConfigOverrides>,
+                ) -> Result<Self, ::dip::core::config::ConfigError> {
+                    let builder = match overrides {
+                        Some(overrides) => overrides.apply(builder.clone())?,
+                        None => builder.clone(),
+                    };
+                    let c = builder
+                        .build()?
+                        .try_deserialize::<'static, #config_name>()?;
+
+                    Ok(c)
+                }
             }
 
+            #[allow(clippy::too_many_arguments)]
             pub fn build_config(
                 builder: ::dip::bevy::ecs::system::Res<::config::builder::ConfigBuilder<::config::builder::DefaultState>>,
-                mut config: Option<::dip::bevy::ecs::system::ResMut<#config_name>>,
+                config: Option<::dip::bevy::ecs::system::ResMut<#config_name>>,
+                fallback: Option<::dip::bevy::ecs::system::Res<::dip::core::config::ConfigFallback<#config_name>>>,
+                exit_on_error: Option<::dip::bevy::ecs::system::Res<::dip::core::config::ConfigExitOnError>>,
+                files: ::dip::bevy::ecs::system::Res<::dip::core::config::ConfigFiles>,
+                overrides: Option<::dip::bevy::ecs::system::Res<::dip::core::config::ConfigOverrides>>,
+                mut errors: ::dip::bevy::ecs::event::EventWriter<::dip::core::config::ConfigError>,
+                mut app_exit: ::dip::bevy::ecs::event::EventWriter<::dip::bevy::app::AppExit>,
+                mut commands: ::dip::bevy::ecs::system::Commands,
+            ) {
+                if config.is_some() {
+                    return;
+                }
+                ::dip::bevy::log::debug!("config files: {:?}", files.loaded());
+
+                match #config_name::load(&builder, overrides.as_deref()) {
+                    Ok(c) => commands.insert_resource(c),
+                    Err(e) => match fallback {
+                        Some(fallback) => {
+                            ::dip::bevy::log::warn!("{}\nfalling back to default config", e.report());
+                            commands.insert_resource((fallback.0)());
+                            commands.insert_resource(e.clone());
+                            errors.send(e);
+                        }
+                        None if exit_on_error.is_some() => {
+                            eprintln!("{}", e.report());
+                            std::process::exit(1);
+                        }
+                        None => {
+                            ::dip::bevy::log::error!("{}", e.report());
+                            commands.insert_resource(e.clone());
+                            errors.send(e);
+                            app_exit.send(::dip::bevy::app::AppExit);
+                        }
+                    },
+                }
+            }
+
+            pub fn reload_config(
+                mut watcher: ::dip::bevy::ecs::system::ResMut<::dip::core::config::ConfigWatcher>,
+                builder: ::dip::bevy::ecs::system::Res<::config::builder::ConfigBuilder<::config::builder::DefaultState>>,
+                overrides: Option<::dip::bevy::ecs::system::Res<::dip::core::config::ConfigOverrides>>,
+                config: Option<::dip::bevy::ecs::system::ResMut<#config_name>>,
+                mut reloaded: ::dip::bevy::ecs::event::EventWriter<::dip::core::config::ConfigReloaded>,
+                mut errors: ::dip::bevy::ecs::event::EventWriter<::dip::core::config::ConfigError>,
                 mut commands: ::dip::bevy::ecs::system::Commands,
             ) {
-                let c = builder
-                    .clone()
-                    .build()
-                    .unwrap()
-                    .try_deserialize::<'static, #config_name>()
-                    .unwrap();
-
-                if config.is_none() {
-                    commands.insert_resource(c);
+                let paths = watcher.changed_files();
+                let mut config = match config {
+                    Some(config) if !paths.is_empty() => config,
+                    _ => return,
+                };
+
+                match #config_name::load(&builder, overrides.as_deref()) {
+                    Ok(c) => {
+                        *config = c;
+                        commands.remove_resource::<::dip::core::config::ConfigError>();
+                        reloaded.send(::dip::core::config::ConfigReloaded { paths });
+                    }
+                    Err(e) => {
+                        ::dip::bevy::log::error!("could not reload config\n{}", e.report());
+                        commands.insert_resource(e.clone());
+                        errors.send(e);
+                    }
                 }
             }
         };
@@ -27,7 +27,7 @@ pub fn ui_action(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     ActionParser::ui_action(input).parse().gen()
 }
 
-#[proc_macro_derive(CliPlugin)]
+#[proc_macro_derive(CliPlugin, attributes(config))]
 pub fn cli_plugin(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as ItemStruct);
 
@@ -3,7 +3,7 @@ use proc_macro::TokenStream;
 use proc_macro2::{TokenStream as TokenStream2, TokenTree};
 use quote::quote;
 use std::str::FromStr;
-use syn::{Field, Fields, ItemEnum, Variant};
+use syn::{Attribute, Field, Fields, Ident, ItemEnum, PathArguments, Type, TypePath, Variant};
 
 pub struct SubcommandParser {
     commands_enum: ItemEnum,
@@ -24,10 +24,10 @@ impl SubcommandParser {
         };
 
         for v in &self.commands_enum.variants {
-            tokens.events.push(self.event(&v));
-            tokens.add_events.push(self.add_event(&v));
-            tokens.event_readers.push(self.event_reader(&v));
-            tokens.handlers.push(self.handler(&v));
+            tokens.events.push(self.event(v));
+            tokens.add_events.push(self.add_event(v));
+            tokens.event_readers.push(self.event_reader(v));
+            tokens.handlers.push(self.handler(v));
         }
 
         tokens
@@ -43,48 +43,24 @@ impl SubcommandParser {
     }
 
     fn add_system(&self) -> TokenStream2 {
-        let mut subsubcommand_handler_names = vec![];
+        let handler_name = self.handler_name();
+        let mut handler = quote! { #handler_name };
+
+        // Run before handlers of nested subcommands so that their events are read in the same
+        // frame. Nested plugins order their own children, which covers any depth.
         for v in self.commands_enum.variants.iter() {
-            for a in v.attrs.iter() {
-                for t in a.tokens.clone().into_iter() {
-                    match t {
-                        TokenTree::Group(g) => {
-                            for s in g.stream() {
-                                match s {
-                                    TokenTree::Ident(ident) => {
-                                        if ident.to_string() == "subcommand" {
-                                            if let syn::Fields::Unnamed(f) = &v.fields {
-                                                let subsubcommand_ty = &f.unnamed[0].ty;
-                                                let subsubcommand_ty_quote =
-                                                    quote! { #subsubcommand_ty };
-                                                let subsubcommand_name = &subsubcommand_ty_quote
-                                                    .to_string()
-                                                    .to_case(Case::Snake);
-
-                                                subsubcommand_handler_names
-                                                    .push(format!("handle_{}", subsubcommand_name));
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
+            if !has_clap_ident(&v.attrs, "subcommand") {
+                continue;
+            }
+            if let Fields::Unnamed(f) = &v.fields {
+                if let Some(nested_handler) = handler_path(&f.unnamed[0].ty) {
+                    handler = quote! { #handler.before(#nested_handler) };
                 }
             }
         }
 
-        let mut handler = self.handler_name().to_string();
-        for n in subsubcommand_handler_names {
-            handler = format!("{}.before({})", handler, n);
-        }
-
-        let handler_token = TokenStream2::from_str(&handler).unwrap();
-
         quote! {
-            .add_startup_system_to_stage(::dip::core::schedule::DipStartupStage::Action, #handler_token);
+            .add_system_to_stage(::dip::core::schedule::DipStage::Action, #handler);
         }
     }
 
@@ -112,16 +88,24 @@ impl SubcommandParser {
                     }
                 }
             }
-            Fields::Unnamed(f) => {
+            Fields::Unnamed(f) if f.unnamed.len() == 1 => {
                 let first_field_ty = &f.unnamed.first().unwrap().ty;
 
                 // add event name alias only when type name is different (if variant_ident != first_field_ty)
-                if &name.to_string() == quote! { #first_field_ty }.to_string().as_str() {
+                if name.to_string() == quote! { #first_field_ty }.to_string().as_str() {
                     quote! {}
                 } else {
                     quote! { pub type #name = #first_field_ty; }
                 }
             }
+            Fields::Unnamed(f) => {
+                let tys = f.unnamed.iter().map(|f| &f.ty);
+
+                quote! {
+                    #[derive(Clone, Debug)]
+                    pub struct #name(#(pub #tys),*);
+                }
+            }
             Fields::Unit => {
                 quote! {
                     #[derive(Clone, Debug)]
@@ -164,31 +148,36 @@ impl SubcommandParser {
 
         match &v.fields {
             Fields::Named(fields) => {
-                let mut field_names = vec![];
-                let mut new_fields = vec![];
-                for f in &fields.named {
-                    let field_name = f.ident.clone().unwrap();
-                    let new_field =
-                        TokenStream2::from_str(&format!("{field_name}: {field_name}.clone()"))
-                            .unwrap();
-
-                    field_names.push(field_name);
-                    new_fields.push(new_field)
-                }
+                let field_names = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.clone().unwrap())
+                    .collect::<Vec<_>>();
 
                 quote! {
-                    #subcommand_ty_name::#ident { #(#field_names)*, } => {
-                        #event_name_snake.send(#event_name { #(#new_fields)*, });
+                    #subcommand_ty_name::#ident { #(#field_names),* } => {
+                        #event_name_snake.send(#event_name { #(#field_names: #field_names.clone()),* });
                     }
                 }
             }
-            Fields::Unnamed(_f) => {
+            Fields::Unnamed(f) if f.unnamed.len() == 1 => {
                 quote! {
                     #subcommand_ty_name::#ident(x) => {
                         #event_name_snake.send(x.clone());
                     }
                 }
             }
+            Fields::Unnamed(f) => {
+                let bindings = (0..f.unnamed.len())
+                    .map(|i| TokenStream2::from_str(&format!("x{i}")).unwrap())
+                    .collect::<Vec<_>>();
+
+                quote! {
+                    #subcommand_ty_name::#ident(#(#bindings),*) => {
+                        #event_name_snake.send(#event_name(#(#bindings.clone()),*));
+                    }
+                }
+            }
             Fields::Unit => {
                 quote! {
                     #subcommand_ty_name::#ident => {
@@ -200,6 +189,37 @@ impl SubcommandParser {
     }
 }
 
+// Whether `#[clap(...)]` attribute contains the ident, e.g. `#[clap(subcommand)]`
+pub(crate) fn has_clap_ident(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("clap"))
+        .flat_map(|a| a.tokens.clone())
+        .any(|t| match t {
+            TokenTree::Group(g) => g.stream().into_iter().any(|s| match s {
+                TokenTree::Ident(ident) => ident == name,
+                _ => false,
+            }),
+            _ => false,
+        })
+}
+
+// `path::to::TodoAction` -> `path::to::handle_todo_action`
+pub(crate) fn handler_path(ty: &Type) -> Option<TokenStream2> {
+    let mut path = match ty {
+        Type::Path(TypePath { path, .. }) => path.clone(),
+        _ => return None,
+    };
+    let last = path.segments.last_mut()?;
+    last.ident = Ident::new(
+        &format!("handle_{}", last.ident.to_string().to_case(Case::Snake)),
+        last.ident.span(),
+    );
+    last.arguments = PathArguments::None;
+
+    Some(quote! { #path })
+}
+
 #[derive(Default)]
 pub struct SubcommandToken {
     handler_name: TokenStream2,
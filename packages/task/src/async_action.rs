@@ -1,31 +1,558 @@
-use std::{fmt::Debug, future::Future};
-use tokio::{runtime::Runtime, sync::mpsc};
+use crate::runtime::AsyncRuntime;
+use futures_util::{
+    stream::{Stream, StreamExt},
+    FutureExt,
+};
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    select,
+    sync::{mpsc, Notify},
+    task::JoinHandle,
+    time::sleep,
+};
+
+type KeyedActions = HashMap<String, (AsyncActionId, Arc<CancelToken>)>;
 
 pub struct AsyncActionPool<Action> {
-    runner: Runtime,
+    runtime: AsyncRuntime,
     tx: mpsc::Sender<Action>,
+    failed_tx: mpsc::UnboundedSender<AsyncActionFailed>,
+    keyed: Arc<Mutex<KeyedActions>>,
 }
 
-impl<Action> AsyncActionPool<Action> {
-    pub fn new(tx: mpsc::Sender<Action>) -> Self {
+impl<Action> AsyncActionPool<Action>
+where
+    Action: Send + 'static,
+{
+    pub fn new(
+        runtime: AsyncRuntime,
+        tx: mpsc::Sender<Action>,
+        failed_tx: mpsc::UnboundedSender<AsyncActionFailed>,
+    ) -> Self {
         Self {
-            runner: Runtime::new().unwrap(),
+            runtime,
             tx,
+            failed_tx,
+            keyed: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    pub fn send<F>(&self, future: F)
+    /// Spawn future and send its output as action. Panic or cancellation is reported as
+    /// [`AsyncActionFailed`] event.
+    pub fn send<F>(&self, future: F) -> AsyncActionHandle
+    where
+        F: Future<Output = Action> + Send + 'static,
+        F::Output: Send + 'static + Debug,
+    {
+        self.send_fallible(async move { Ok::<_, AsyncActionError>(future.await) })
+    }
+
+    /// Same as [`send`](Self::send) but `Err` output is reported as [`AsyncActionFailed`] event
+    /// instead of being sent as action.
+    pub fn send_fallible<F, E>(&self, future: F) -> AsyncActionHandle
+    where
+        F: Future<Output = Result<Action, E>> + Send + 'static,
+        E: Display + 'static,
+    {
+        self.spawn(self.deliver(future), None, None)
+    }
+
+    /// Spawn stream and send every item as action. Useful to report progress of long running
+    /// task. Methods returning `impl Stream<Item = T>` in `#[async_action]` produce such streams.
+    pub fn send_stream<S>(&self, stream: S) -> AsyncActionHandle
+    where
+        S: Stream<Item = Action> + Send + 'static,
+    {
+        let tx = self.tx.clone();
+
+        self.spawn(
+            async move {
+                let mut stream = Box::pin(stream);
+                while let Some(action) = stream.next().await {
+                    tx.send(action)
+                        .await
+                        .map_err(|_| AsyncActionError::ChannelClosed)?;
+                }
+                Ok(())
+            },
+            None,
+            None,
+        )
+    }
+
+    /// Spawn future under the key. [`KeyedPolicy`] decides what happens when another task with
+    /// the same key is still running. Returns `None` when the future is ignored.
+    ///
+    /// Superseded tasks are cancelled without emitting [`AsyncActionFailed`], so only the latest
+    /// result is delivered.
+    ///
+    /// ```no_run
+    /// # use dip_task::{AsyncActionPool, KeyedPolicy};
+    /// # async fn search(query: String) -> String { query }
+    /// fn on_input(async_action: &AsyncActionPool<String>, query: String) {
+    ///     async_action.send_keyed("search", KeyedPolicy::CancelPrevious, search(query));
+    /// }
+    /// ```
+    pub fn send_keyed<F>(
+        &self,
+        key: impl Into<String>,
+        policy: KeyedPolicy,
+        future: F,
+    ) -> Option<AsyncActionHandle>
     where
         F: Future<Output = Action> + Send + 'static,
         F::Output: Send + 'static + Debug,
+    {
+        let key = key.into();
+        let mut keyed = self.keyed.lock().unwrap();
+
+        if let Some((_, running)) = keyed.get(&key) {
+            match policy {
+                KeyedPolicy::IgnoreIfRunning => return None,
+                KeyedPolicy::CancelPrevious | KeyedPolicy::Debounce(_) => running.supersede(),
+            }
+        }
+
+        let delay = match policy {
+            KeyedPolicy::Debounce(delay) => Some(delay),
+            _ => None,
+        };
+        let handle = self.spawn(
+            self.deliver(async move { Ok::<_, AsyncActionError>(future.await) }),
+            Some(key.clone()),
+            delay,
+        );
+        keyed.insert(key, (handle.id, handle.cancel.clone()));
+
+        Some(handle)
+    }
+
+    /// Cancel running task with the key
+    pub fn cancel_keyed(&self, key: &str) {
+        if let Some((_, running)) = self.keyed.lock().unwrap().get(key) {
+            running.cancel();
+        }
+    }
+
+    // Send output of the future as action
+    fn deliver<F, E>(&self, future: F) -> impl Future<Output = Result<(), AsyncActionError>>
+    where
+        F: Future<Output = Result<Action, E>> + Send + 'static,
+        E: Display + 'static,
     {
         let tx = self.tx.clone();
 
-        self.runner.spawn(async move {
-            let task = future.await;
-            tx.send(task).await.unwrap();
+        async move {
+            let action = future
+                .await
+                .map_err(|e| AsyncActionError::Error(e.to_string()))?;
+            tx.send(action)
+                .await
+                .map_err(|_| AsyncActionError::ChannelClosed)
+        }
+    }
+
+    fn spawn<F>(&self, task: F, key: Option<String>, delay: Option<Duration>) -> AsyncActionHandle
+    where
+        F: Future<Output = Result<(), AsyncActionError>> + Send + 'static,
+    {
+        let id = AsyncActionId::new();
+        let cancel = Arc::new(CancelToken::default());
+        let failed_tx = self.failed_tx.clone();
+        let keyed = self.keyed.clone();
+
+        let task = AssertUnwindSafe(async move {
+            if let Some(delay) = delay {
+                sleep(delay).await;
+            }
+            task.await
+        })
+        .catch_unwind();
+        let cancelled = cancel.clone();
+        let join = self.runtime.spawn(async move {
+            // Task runs in the same tokio task and cancellation is checked before every poll of
+            // it, so cancelled or superseded task never delivers its action
+            let result = select! {
+                biased;
+                () = cancelled.notify.notified() => Err(AsyncActionError::Cancelled),
+                result = task => match result {
+                    Ok(result) => result,
+                    Err(payload) => Err(AsyncActionError::Panicked(panic_message(payload))),
+                },
+            };
+
+            if let Some(key) = key {
+                let mut keyed = keyed.lock().unwrap();
+                if matches!(keyed.get(&key), Some((running_id, _)) if *running_id == id) {
+                    keyed.remove(&key);
+                }
+            }
+
+            match &result {
+                Err(_) if cancelled.superseded.load(Ordering::Relaxed) => {}
+                Err(error) => {
+                    let failed = AsyncActionFailed {
+                        id,
+                        error: error.clone(),
+                    };
+                    if failed_tx.send(failed).is_err() {
+                        log::error!("AsyncAction {id} failed: {error}");
+                    }
+                }
+                Ok(()) => {}
+            }
+
+            result
         });
+
+        AsyncActionHandle { id, cancel, join }
+    }
+}
+
+/// What to do when keyed task is sent while another task with the same key is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyedPolicy {
+    /// Cancel running task and start new one
+    CancelPrevious,
+    /// Keep running task and drop new one
+    IgnoreIfRunning,
+    /// Cancel running task and start new one after the delay. Only the last task sent within the
+    /// delay runs.
+    Debounce(Duration),
+}
+
+#[derive(Default)]
+struct CancelToken {
+    notify: Notify,
+    superseded: AtomicBool,
+}
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.notify.notify_one();
+    }
+
+    fn supersede(&self) {
+        self.superseded.store(true, Ordering::Relaxed);
+        self.cancel();
     }
 }
 
 pub type NoAsyncAction = ();
+
+/// Unique id assigned to each spawned async action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AsyncActionId(u64);
+
+impl AsyncActionId {
+    fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Display for AsyncActionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Reason why async action didn't deliver its action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsyncActionError {
+    /// Future returned `Err`
+    Error(String),
+    /// Future panicked
+    Panicked(String),
+    /// Cancelled with [`AsyncActionHandle::cancel`]
+    Cancelled,
+    /// Action receiver is already dropped
+    ChannelClosed,
+}
+
+impl Display for AsyncActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsyncActionError::Error(e) => write!(f, "{e}"),
+            AsyncActionError::Panicked(e) => write!(f, "panicked: {e}"),
+            AsyncActionError::Cancelled => write!(f, "cancelled"),
+            AsyncActionError::ChannelClosed => write!(f, "action channel is closed"),
+        }
+    }
+}
+
+impl std::error::Error for AsyncActionError {}
+
+/// Event emitted when async action fails
+#[derive(Debug, Clone)]
+pub struct AsyncActionFailed {
+    /// Id of the failed async action
+    pub id: AsyncActionId,
+    /// Failure reason
+    pub error: AsyncActionError,
+}
+
+/// Handle returned from [`AsyncActionPool::send`]. Dropping it doesn't cancel the task. Await it
+/// to wait until action is delivered.
+pub struct AsyncActionHandle {
+    id: AsyncActionId,
+    cancel: Arc<CancelToken>,
+    join: JoinHandle<Result<(), AsyncActionError>>,
+}
+
+impl AsyncActionHandle {
+    /// Id of the async action
+    pub fn id(&self) -> AsyncActionId {
+        self.id
+    }
+
+    /// Cancel the task. [`AsyncActionFailed`] with [`AsyncActionError::Cancelled`] is emitted
+    /// unless it is already finished.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Debug for AsyncActionHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncActionHandle")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl Future for AsyncActionHandle {
+    type Output = Result<(), AsyncActionError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.join)
+            .poll(cx)
+            .map(|result| match result {
+                Ok(result) => result,
+                Err(_) => Err(AsyncActionError::Cancelled),
+            })
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        runtime::Handle,
+        sync::{mpsc::error::TryRecvError, oneshot},
+    };
+
+    type Receivers = (
+        mpsc::Receiver<u32>,
+        mpsc::UnboundedReceiver<AsyncActionFailed>,
+    );
+
+    fn pool() -> (AsyncActionPool<u32>, Receivers) {
+        let (tx, rx) = mpsc::channel(8);
+        let (failed_tx, failed_rx) = mpsc::unbounded_channel();
+        let pool =
+            AsyncActionPool::new(AsyncRuntime::from_handle(Handle::current()), tx, failed_tx);
+
+        (pool, (rx, failed_rx))
+    }
+
+    #[tokio::test]
+    async fn send_delivers_action() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        assert_eq!(pool.send(async { 1 }).await, Ok(()));
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert!(matches!(failed_rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn error_is_reported() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        let handle = pool.send_fallible(async { Err::<u32, _>("boom") });
+        let id = handle.id();
+        let error = AsyncActionError::Error("boom".to_string());
+
+        assert_eq!(handle.await, Err(error.clone()));
+        let failed = failed_rx.try_recv().unwrap();
+        assert_eq!((failed.id, failed.error), (id, error));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn panic_is_reported() {
+        let (pool, (_rx, mut failed_rx)) = pool();
+
+        let error = AsyncActionError::Panicked("boom".to_string());
+        assert_eq!(
+            pool.send(async { panic!("boom") }).await,
+            Err(error.clone())
+        );
+        assert_eq!(failed_rx.try_recv().unwrap().error, error);
+    }
+
+    #[tokio::test]
+    async fn cancelled_handle_is_reported() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        let handle = pool.send(std::future::pending::<u32>());
+        handle.cancel();
+
+        assert_eq!(handle.await, Err(AsyncActionError::Cancelled));
+        assert_eq!(
+            failed_rx.try_recv().unwrap().error,
+            AsyncActionError::Cancelled
+        );
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn closed_channel_is_reported() {
+        let (pool, (rx, mut failed_rx)) = pool();
+        drop(rx);
+
+        assert_eq!(
+            pool.send(async { 1 }).await,
+            Err(AsyncActionError::ChannelClosed)
+        );
+        assert_eq!(
+            failed_rx.try_recv().unwrap().error,
+            AsyncActionError::ChannelClosed
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_items_are_delivered() {
+        let (pool, (mut rx, _failed_rx)) = pool();
+
+        assert_eq!(
+            pool.send_stream(futures_util::stream::iter([1, 2])).await,
+            Ok(())
+        );
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+    }
+
+    // Future that resolves to `value` once the sender is used or dropped
+    fn gated(value: u32) -> (oneshot::Sender<()>, impl Future<Output = u32>) {
+        let (tx, rx) = oneshot::channel();
+        (tx, async move {
+            let _ = rx.await;
+            value
+        })
+    }
+
+    #[tokio::test]
+    async fn cancel_previous_delivers_only_latest() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        let (gate, first) = gated(1);
+        let first = pool
+            .send_keyed("search", KeyedPolicy::CancelPrevious, first)
+            .unwrap();
+        let second = pool
+            .send_keyed("search", KeyedPolicy::CancelPrevious, async { 2 })
+            .unwrap();
+        let _ = gate.send(());
+
+        assert_eq!(first.await, Err(AsyncActionError::Cancelled));
+        assert_eq!(second.await, Ok(()));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert!(matches!(failed_rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn ignore_if_running_keeps_running_task() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        let (gate, first) = gated(1);
+        let first = pool
+            .send_keyed("search", KeyedPolicy::IgnoreIfRunning, first)
+            .unwrap();
+        assert!(pool
+            .send_keyed("search", KeyedPolicy::IgnoreIfRunning, async { 2 })
+            .is_none());
+        let _ = gate.send(());
+
+        assert_eq!(first.await, Ok(()));
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert!(matches!(failed_rx.try_recv(), Err(TryRecvError::Empty)));
+
+        // Key is released once the task finishes
+        let third = pool.send_keyed("search", KeyedPolicy::IgnoreIfRunning, async { 3 });
+        assert_eq!(third.unwrap().await, Ok(()));
+        assert_eq!(rx.try_recv(), Ok(3));
+    }
+
+    #[tokio::test]
+    async fn debounce_delivers_only_last() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+        let policy = KeyedPolicy::Debounce(Duration::from_millis(20));
+
+        let handles = (1..=3)
+            .map(|value| {
+                pool.send_keyed("search", policy, async move { value })
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = vec![];
+        for handle in handles {
+            results.push(handle.await);
+        }
+        assert_eq!(
+            results,
+            vec![
+                Err(AsyncActionError::Cancelled),
+                Err(AsyncActionError::Cancelled),
+                Ok(())
+            ]
+        );
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert!(matches!(failed_rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn cancel_keyed_is_reported() {
+        let (pool, (mut rx, mut failed_rx)) = pool();
+
+        let (_gate, future) = gated(1);
+        let handle = pool
+            .send_keyed("search", KeyedPolicy::CancelPrevious, future)
+            .unwrap();
+        pool.cancel_keyed("search");
+
+        assert_eq!(handle.await, Err(AsyncActionError::Cancelled));
+        assert_eq!(
+            failed_rx.try_recv().unwrap().error,
+            AsyncActionError::Cancelled
+        );
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
@@ -1,8 +1,17 @@
 mod async_action;
+mod runtime;
 
-pub use crate::async_action::{AsyncActionPool, NoAsyncAction};
+pub use crate::async_action::{
+    AsyncActionError, AsyncActionFailed, AsyncActionHandle, AsyncActionId, AsyncActionPool,
+    KeyedPolicy, NoAsyncAction,
+};
+pub use crate::runtime::{AsyncRuntime, AsyncRuntimeSettings, BlockOnError};
 pub use dip_macro::async_action;
+pub use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
 
 pub mod prelude {
-    pub use crate::{async_action, AsyncActionPool, NoAsyncAction};
+    pub use crate::{
+        async_action, AsyncActionError, AsyncActionFailed, AsyncActionHandle, AsyncActionPool,
+        AsyncRuntime, AsyncRuntimeSettings, KeyedPolicy, NoAsyncAction,
+    };
 }
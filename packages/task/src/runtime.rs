@@ -0,0 +1,240 @@
+use std::{
+    fmt::{self, Debug, Display},
+    future::Future,
+    panic,
+    sync::Arc,
+    thread,
+};
+use tokio::{
+    runtime::{Builder, Handle, Runtime, RuntimeFlavor},
+    sync::oneshot,
+    task::{self, JoinHandle},
+};
+
+/// Configure tokio runtime shared by dip plugins. Insert it as resource before adding plugins.
+#[derive(Debug, Clone)]
+pub struct AsyncRuntimeSettings {
+    /// Number of worker threads. Defaults to number of CPU cores.
+    pub worker_threads: Option<usize>,
+    /// Name of the threads spawned by the runtime
+    pub thread_name: String,
+    /// Use single threaded runtime driven by one background thread
+    pub current_thread: bool,
+}
+
+impl Default for AsyncRuntimeSettings {
+    fn default() -> Self {
+        Self {
+            worker_threads: None,
+            thread_name: "dip-runtime".to_string(),
+            current_thread: false,
+        }
+    }
+}
+
+/// Tokio runtime shared across dip subsystems: DesktopPlugin, VirtualDom threads,
+/// AsyncActionPool and CLI runner.
+///
+/// To embed dip into an app that already owns a runtime, insert
+/// `AsyncRuntime::from_handle(Handle::current())` as resource before adding plugins. The runtime
+/// needs to be multi-threaded if the app runs on one of its worker threads. Inside
+/// `#[tokio::main(flavor = "current_thread")]`, keep the default runtime built by dip instead.
+#[derive(Clone)]
+pub struct AsyncRuntime {
+    handle: Handle,
+    _owned: Option<Arc<OwnedRuntime>>,
+}
+
+impl AsyncRuntime {
+    /// Build new runtime
+    pub fn new(settings: &AsyncRuntimeSettings) -> Self {
+        let mut builder = if settings.current_thread {
+            Builder::new_current_thread()
+        } else {
+            Builder::new_multi_thread()
+        };
+        builder
+            .enable_all()
+            .thread_name(settings.thread_name.clone());
+        if let Some(worker_threads) = settings.worker_threads {
+            builder.worker_threads(worker_threads);
+        }
+        let runtime = builder.build().unwrap();
+        let handle = runtime.handle().clone();
+
+        let owned = if settings.current_thread {
+            // Current thread runtime only makes progress while someone blocks on it
+            let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+            std::thread::Builder::new()
+                .name(settings.thread_name.clone())
+                .spawn(move || {
+                    let _ = runtime.block_on(shutdown_rx);
+                })
+                .unwrap();
+
+            OwnedRuntime {
+                runtime: None,
+                shutdown_tx: Some(shutdown_tx),
+            }
+        } else {
+            OwnedRuntime {
+                runtime: Some(runtime),
+                shutdown_tx: None,
+            }
+        };
+
+        Self {
+            handle,
+            _owned: Some(Arc::new(owned)),
+        }
+    }
+
+    /// Use runtime owned by someone else
+    pub fn from_handle(handle: Handle) -> Self {
+        Self {
+            handle,
+            _owned: None,
+        }
+    }
+
+    /// Handle of the runtime
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Spawn future on the runtime
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.handle.spawn(future)
+    }
+
+    /// Run future to completion, blocking the current thread.
+    ///
+    /// Tokio doesn't allow blocking inside async context, so when called from a worker of a
+    /// multi-threaded runtime, e.g. an app driven inside `#[tokio::main]`, the worker is turned
+    /// into a blocking thread first. Inside a current_thread runtime, the future runs on a
+    /// dedicated thread instead. That only works if this runtime is driven by other threads, so
+    /// [`BlockOnError`] is returned when this runtime is a borrowed current_thread runtime.
+    pub fn block_on<F>(&self, future: F) -> Result<F::Output, BlockOnError>
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        let current = match Handle::try_current() {
+            Ok(current) => current,
+            Err(_) => return Ok(self.handle.block_on(future)),
+        };
+
+        match current.runtime_flavor() {
+            RuntimeFlavor::CurrentThread => {
+                if self._owned.is_none()
+                    && self.handle.runtime_flavor() == RuntimeFlavor::CurrentThread
+                {
+                    return Err(BlockOnError);
+                }
+
+                thread::scope(|scope| {
+                    scope
+                        .spawn(|| self.handle.block_on(future))
+                        .join()
+                        .map_err(|payload| panic::resume_unwind(payload))
+                })
+            }
+            _ => Ok(task::block_in_place(|| self.handle.block_on(future))),
+        }
+    }
+}
+
+impl Default for AsyncRuntime {
+    fn default() -> Self {
+        Self::new(&AsyncRuntimeSettings::default())
+    }
+}
+
+impl Debug for AsyncRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncRuntime")
+            .field("owned", &self._owned.is_some())
+            .finish()
+    }
+}
+
+/// Returned from [`AsyncRuntime::block_on`] called inside the borrowed current_thread runtime.
+/// Blocking there would stop the only thread that drives the runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockOnError;
+
+impl Display for BlockOnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot block inside current_thread runtime which AsyncRuntime borrows, use default \
+             AsyncRuntime or multi-threaded runtime instead"
+        )
+    }
+}
+
+impl std::error::Error for BlockOnError {}
+
+struct OwnedRuntime {
+    runtime: Option<Runtime>,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for OwnedRuntime {
+    fn drop(&mut self) {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            let _ = shutdown_tx.send(());
+        }
+        // Last reference can be dropped inside async context
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    async fn answer() -> u32 {
+        sleep(Duration::from_millis(1)).await;
+        42
+    }
+
+    #[test]
+    fn block_on_outside_runtime() {
+        assert_eq!(AsyncRuntime::default().block_on(answer()), Ok(42));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn block_on_inside_multi_thread_runtime() {
+        let runtime = AsyncRuntime::from_handle(Handle::current());
+
+        assert_eq!(runtime.block_on(answer()), Ok(42));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn block_on_inside_current_thread_runtime() {
+        let multi_thread = AsyncRuntime::default();
+        let current_thread = AsyncRuntime::new(&AsyncRuntimeSettings {
+            current_thread: true,
+            ..Default::default()
+        });
+
+        assert_eq!(multi_thread.block_on(answer()), Ok(42));
+        assert_eq!(current_thread.block_on(answer()), Ok(42));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn block_on_borrowed_current_thread_runtime_is_error() {
+        let runtime = AsyncRuntime::from_handle(Handle::current());
+
+        assert_eq!(runtime.block_on(answer()), Err(BlockOnError));
+    }
+}
@@ -14,10 +14,12 @@ pub use bevy;
 #[cfg(feature = "desktop")]
 pub use dioxus;
 
-///
+/// Commonly used items of bevy, dioxus and dip
 pub mod prelude {
     pub use bevy::prelude::*;
     pub use dioxus::prelude::*;
+    // Both preludes export `Component`. ECS component is the one derived in apps.
+    pub use bevy::prelude::Component;
     pub use dip_core::prelude::*;
     pub use dip_macro::{ui_action, ui_state, ConfigPlugin};
 
@@ -2,13 +2,16 @@ mod plugin;
 mod resource;
 
 use crate::plugin::{ActionPlugin, AsyncAction, CliPlugin, ToolPlugin};
-use dip::bevy::{app::App, log::LogPlugin};
+use dip::{
+    bevy::{app::App, log::LogPlugin},
+    cli::tool::ToolRegistry,
+};
 
 fn main() {
     App::new()
         .add_plugin(CliPlugin::<AsyncAction>::continuous())
         .add_plugin(ActionPlugin)
-        .add_plugin(ToolPlugin)
+        .add_plugin(ToolPlugin::new(ToolRegistry::default()))
         .add_plugin(LogPlugin)
         .run();
 }
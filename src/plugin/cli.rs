@@ -18,6 +18,28 @@ pub enum Action {
 
 #[derive(SubcommandPlugin, clap::Subcommand, Clone, Debug)]
 pub enum ToolAction {
-    List,
-    Add { name: String },
+    List {
+        /// Show versions installed on disk
+        #[clap(long)]
+        installed: bool,
+    },
+    Add {
+        /// Tool name, optionally with version: `name@version`
+        name: String,
+
+        /// Install even if no checksum is published for the tool
+        #[clap(long)]
+        skip_checksum: bool,
+    },
+    /// Install tools declared in dip.toml and record them in dip.lock
+    Sync {
+        /// Install even if no checksum is published for the tool
+        #[clap(long)]
+        skip_checksum: bool,
+    },
+    /// Pick active version of an installed tool
+    Use {
+        /// Tool name with version: `name@version`
+        name: String,
+    },
 }
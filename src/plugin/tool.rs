@@ -1,34 +1,82 @@
 use crate::{
     plugin::cli::*,
-    resource::tool::{Tool, ToolResult},
+    resource::{
+        manifest::{Lockfile, Manifest},
+        tool::{InstallProgress, Tool, ToolResult},
+    },
 };
+use anyhow::anyhow;
 use dip::{
     bevy::{
         app::{App, AppExit, Plugin},
         ecs::{
             event::{EventReader, EventWriter},
-            system::Res,
+            system::{Commands, Res, ResMut},
         },
     },
-    core::task::{async_action, AsyncActionPool},
+    cli::{tool::ToolRegistry, AppExitCode},
+    core::task::{async_action, AsyncActionPool, Stream},
 };
+use std::collections::BTreeSet;
+
+/// Manage tools registered in the registry
+pub struct ToolPlugin {
+    registry: ToolRegistry,
+}
 
-pub struct ToolPlugin;
+impl ToolPlugin {
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self { registry }
+    }
+}
 
 impl Plugin for ToolPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(ToolActionPlugin)
+        app.insert_resource(self.registry.clone())
+            .add_plugin(ToolActionPlugin)
             .add_plugin(AsyncActionPlugin)
             .add_system(handle_list_tool)
             .add_system(handle_add_tool)
+            .add_system(handle_use_tool)
+            .add_system(handle_sync_tool)
             .add_system(handle_install);
     }
 }
 
-fn handle_list_tool(mut events: EventReader<ListToolAction>, mut app_exit: EventWriter<AppExit>) {
-    for _ in events.iter() {
-        for t in Tool::list().iter() {
-            println!("- {t}");
+/// Tools still being installed by `dip tool sync`
+struct ToolSync {
+    manifest: Manifest,
+    pending: BTreeSet<&'static str>,
+    // Tools whose download line is already printed
+    downloading: BTreeSet<&'static str>,
+}
+
+fn handle_list_tool(
+    mut events: EventReader<ListToolAction>,
+    registry: Res<ToolRegistry>,
+    mut app_exit: EventWriter<AppExit>,
+    mut app_exit_code: EventWriter<AppExitCode>,
+) {
+    for e in events.iter() {
+        for t in registry.list().iter() {
+            if !e.installed {
+                println!("- {t}");
+                continue;
+            }
+
+            let spec = registry.get(t).unwrap();
+            match Tool::installed(spec) {
+                Ok(tools) => {
+                    for tool in tools.iter() {
+                        let active = if tool.is_active() { " (active)" } else { "" };
+                        println!("- {tool}{active}");
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{:?}", e.error);
+                    app_exit_code.send(AppExitCode::FAILURE);
+                }
+            }
         }
         app_exit.send(AppExit);
     }
@@ -36,38 +84,215 @@ fn handle_list_tool(mut events: EventReader<ListToolAction>, mut app_exit: Event
 
 fn handle_add_tool(
     mut events: EventReader<AddToolAction>,
+    registry: Res<ToolRegistry>,
+    async_action: Res<AsyncActionPool<AsyncAction>>,
+    mut app_exit_code: EventWriter<AppExitCode>,
+) {
+    for e in events.iter() {
+        match Tool::parse(&registry, &e.name) {
+            Ok(tool) => {
+                let tool = tool.skip_checksum(e.skip_checksum);
+                async_action.send_stream(AsyncAction::install(tool));
+            }
+            Err(e) => {
+                eprintln!("{:?}", e.error);
+                app_exit_code.send(AppExitCode::FAILURE);
+            }
+        }
+    }
+}
+
+fn handle_use_tool(
+    mut events: EventReader<UseToolAction>,
+    registry: Res<ToolRegistry>,
+    mut app_exit: EventWriter<AppExit>,
+    mut app_exit_code: EventWriter<AppExitCode>,
+) {
+    for e in events.iter() {
+        match Tool::parse(&registry, &e.name).and_then(|tool| tool.activate().map(|_| tool)) {
+            Ok(tool) => {
+                println!("Using {tool}");
+                app_exit.send(AppExit);
+            }
+            Err(e) => {
+                eprintln!("{:?}", e.error);
+                app_exit_code.send(AppExitCode::FAILURE);
+            }
+        }
+    }
+}
+
+fn handle_sync_tool(
+    mut events: EventReader<SyncToolAction>,
+    registry: Res<ToolRegistry>,
     async_action: Res<AsyncActionPool<AsyncAction>>,
+    mut commands: Commands,
+    mut app_exit: EventWriter<AppExit>,
+    mut app_exit_code: EventWriter<AppExitCode>,
 ) {
     for e in events.iter() {
-        let name = e.name.as_str();
-        let tool = Tool::from_str(name).expect(&format!("Could not find tool: {name}"));
+        let manifest = match Manifest::load_project() {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                eprintln!("{:?}", e.error);
+                app_exit_code.send(AppExitCode::FAILURE);
+                continue;
+            }
+        };
+        if manifest.tools.is_empty() {
+            eprintln!("Could not find tools in dip.toml");
+            app_exit_code.send(AppExitCode::FAILURE);
+            continue;
+        }
+
+        let missing = match start_sync(&manifest, &registry, e.skip_checksum) {
+            Ok(missing) => missing,
+            Err(e) => {
+                eprintln!("{:?}", e.error);
+                app_exit_code.send(AppExitCode::FAILURE);
+                continue;
+            }
+        };
+
+        if missing.is_empty() {
+            match finish_sync(&manifest, &registry) {
+                Ok(()) => app_exit.send(AppExit),
+                Err(e) => {
+                    eprintln!("{:?}", e.error);
+                    app_exit_code.send(AppExitCode::FAILURE);
+                }
+            }
+            continue;
+        }
+
+        let pending = missing.iter().map(Tool::name).collect();
+        for tool in missing {
+            async_action.send_stream(AsyncAction::install(tool));
+        }
+        commands.insert_resource(ToolSync {
+            manifest,
+            pending,
+            downloading: BTreeSet::new(),
+        });
+    }
+}
+
+// Tools declared in dip.toml
+fn manifest_tools(manifest: &Manifest, registry: &ToolRegistry) -> ToolResult<Vec<Tool>> {
+    manifest
+        .tools
+        .iter()
+        .map(|(name, version)| Tool::parse(registry, &format!("{name}@{version}")))
+        .collect()
+}
 
-        match tool {
-            Tool::Tailwind => async_action.send(AsyncAction::install(tool)),
+// Returns tools which are not installed yet, expecting digests in dip.lock
+fn start_sync(
+    manifest: &Manifest,
+    registry: &ToolRegistry,
+    skip_checksum: bool,
+) -> ToolResult<Vec<Tool>> {
+    let lockfile = Lockfile::load()?;
+
+    let mut missing = vec![];
+    for tool in manifest_tools(manifest, registry)? {
+        let locked = lockfile.checksum(&tool);
+        if !tool.is_installed() {
+            missing.push(tool.skip_checksum(skip_checksum).locked_checksum(locked));
+        } else if let (Some(locked), Some(installed)) = (locked, tool.installed_checksum()) {
+            if locked != installed {
+                Err(anyhow!("Installed {tool} does not match dip.lock"))?
+            }
         }
     }
+
+    Ok(missing)
+}
+
+// Activate versions declared in dip.toml and record them in dip.lock
+fn finish_sync(manifest: &Manifest, registry: &ToolRegistry) -> ToolResult<()> {
+    let tools = manifest_tools(manifest, registry)?;
+    for tool in tools.iter() {
+        tool.activate()?;
+        println!("Using {tool}");
+    }
+
+    let mut lockfile = Lockfile::load()?;
+    for tool in lockfile.lock(&tools)? {
+        eprintln!(
+            "Skipped locking {tool}: digest of its download is unknown. Reinstall it to lock."
+        );
+    }
+    lockfile.save()?;
+
+    Ok(())
 }
 
 fn handle_install(
-    mut events: EventReader<ToolResult<Install>>,
+    mut events: EventReader<ToolResult<InstallProgress>>,
+    registry: Res<ToolRegistry>,
+    mut sync: Option<ResMut<ToolSync>>,
     mut app_exit: EventWriter<AppExit>,
+    mut app_exit_code: EventWriter<AppExitCode>,
 ) {
     for e in events.iter() {
-        if let Err(e) = e {
-            println!("{:?}", e.error);
+        match e {
+            Ok(progress) if progress.finished => {
+                let sync = match sync.as_deref_mut() {
+                    Some(sync) => sync,
+                    None => {
+                        println!("\nInstalled {}@{}", progress.name, progress.version);
+                        app_exit.send(AppExit);
+                        continue;
+                    }
+                };
+                println!("Installed {}@{}", progress.name, progress.version);
+
+                // Wait for the rest of concurrent installs
+                sync.pending.remove(progress.name);
+                if sync.pending.is_empty() {
+                    match finish_sync(&sync.manifest, &registry) {
+                        Ok(()) => app_exit.send(AppExit),
+                        Err(e) => {
+                            eprintln!("{:?}", e.error);
+                            app_exit_code.send(AppExitCode::FAILURE);
+                        }
+                    }
+                }
+            }
+            Ok(progress) => match sync.as_deref_mut() {
+                // Concurrent installs would overwrite each other's progress, so print once per
+                // tool
+                Some(sync) => {
+                    if sync.downloading.insert(progress.name) {
+                        println!("Downloading {}@{}", progress.name, progress.version);
+                    }
+                }
+                None => {
+                    match progress.total {
+                        Some(total) => print!(
+                            "\rDownloading {}@{}: {} / {} bytes",
+                            progress.name, progress.version, progress.downloaded, total
+                        ),
+                        None => print!(
+                            "\rDownloading {}@{}: {} bytes",
+                            progress.name, progress.version, progress.downloaded
+                        ),
+                    }
+                    let _ = std::io::Write::flush(&mut std::io::stdout());
+                }
+            },
+            Err(e) => {
+                eprintln!("{:?}", e.error);
+                app_exit_code.send(AppExitCode::FAILURE);
+            }
         }
-        app_exit.send(AppExit);
     }
 }
 
 #[async_action]
 impl AsyncActionCreator {
-    async fn install(tool: Tool) -> ToolResult<Install> {
-        tool.install().await?;
-
-        Ok(Install)
+    fn install(tool: Tool) -> impl Stream<Item = ToolResult<InstallProgress>> {
+        tool.install()
     }
 }
-
-#[derive(Clone, Debug)]
-pub struct Install;
@@ -1,2 +1,2 @@
-mod platform;
+pub mod manifest;
 pub mod tool;
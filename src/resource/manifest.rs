@@ -0,0 +1,137 @@
+use crate::resource::tool::{Tool, ToolResult};
+use anyhow::{anyhow, Context};
+use dip::core::config::ConfigPlugin;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Project-local `dip.toml`
+///
+/// ```toml
+/// [tools]
+/// tailwindcss = "3.2.0"
+/// esbuild = "0.15.10"
+/// ```
+#[derive(ConfigPlugin, Deserialize, Debug, Default)]
+pub struct Manifest {
+    /// Tool name to version
+    #[serde(default)]
+    pub tools: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Build from `dip.toml` and `DIP_*` env vars like ConfigPlugin does. Read only when needed
+    /// by `dip tool sync`, so the plugin is not added to the app.
+    pub fn load_project() -> ToolResult<Self> {
+        let plugin = ConfigPlugin::new()
+            .app_name("dip")
+            .env_prefix("DIP")
+            .search_paths(vec!["dip"]);
+
+        let manifest = Self::load(&Self::builder(&plugin), None)
+            .map_err(|e| anyhow!("Failed to load dip.toml\n{}", e.report()))?;
+
+        Ok(manifest)
+    }
+}
+
+/// `dip.lock` next to `dip.toml`. Records resolved download url and digest of each tool for
+/// every platform it was synced on.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Lockfile {
+    #[serde(default, rename = "tool")]
+    pub tools: Vec<LockedTool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LockedTool {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub arch: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+impl Lockfile {
+    pub fn path() -> PathBuf {
+        PathBuf::from("dip.lock")
+    }
+
+    /// Empty lockfile if it does not exist yet
+    pub fn load() -> ToolResult<Self> {
+        let path = Self::path();
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read dip.lock")?;
+        let lockfile = toml::from_str(&content).context("Failed to parse dip.lock")?;
+
+        Ok(lockfile)
+    }
+
+    pub fn save(&self) -> ToolResult<()> {
+        let content = toml::to_string(self).context("Failed to serialize dip.lock")?;
+        fs::write(Self::path(), content).context("Failed to write dip.lock")?;
+
+        Ok(())
+    }
+
+    /// Locked digest of the tool if version and target match
+    pub fn checksum(&self, tool: &Tool) -> Option<String> {
+        self.tools
+            .iter()
+            .find(|locked| locked.is_for(tool) && locked.version == tool.version())
+            .map(|locked| locked.sha256.clone())
+    }
+
+    /// Record installed tools for the current target. Entries of other targets are kept as long
+    /// as they lock the same version, and tools no longer given are dropped. Returns tools
+    /// installed without recorded digest, e.g. by an older dip. Their entries are left as is.
+    pub fn lock<'a>(&mut self, tools: &'a [Tool]) -> ToolResult<Vec<&'a Tool>> {
+        let mut locked = vec![];
+        let mut unknown = vec![];
+        for tool in tools {
+            let sha256 = match tool.installed_checksum() {
+                Some(sha256) => sha256,
+                None => {
+                    unknown.push(tool);
+                    continue;
+                }
+            };
+
+            locked.push(LockedTool {
+                name: tool.name().to_string(),
+                version: tool.version().to_string(),
+                platform: tool.target().platform.as_str().to_string(),
+                arch: tool.target().arch.as_str().to_string(),
+                url: tool.download_url()?,
+                sha256,
+            });
+        }
+
+        self.tools.retain(|entry| {
+            let declared = tools
+                .iter()
+                .any(|tool| entry.name == tool.name() && entry.version == tool.version());
+            let replaced = locked.iter().any(|new: &LockedTool| {
+                entry.name == new.name && entry.platform == new.platform && entry.arch == new.arch
+            });
+            declared && !replaced
+        });
+        self.tools.extend(locked);
+        self.tools
+            .sort_by(|a, b| (&a.name, &a.platform, &a.arch).cmp(&(&b.name, &b.platform, &b.arch)));
+
+        Ok(unknown)
+    }
+}
+
+impl LockedTool {
+    // Same tool on the same target, regardless of version
+    fn is_for(&self, tool: &Tool) -> bool {
+        self.name == tool.name()
+            && self.platform == tool.target().platform.as_str()
+            && self.arch == tool.target().arch.as_str()
+    }
+}
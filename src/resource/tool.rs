@@ -1,11 +1,23 @@
-use crate::resource::platform::Platform;
 use anyhow::{anyhow, Context};
+use dip::{
+    cli::tool::{ArchiveType, AssetDigest, AssetHasher, Checksum, Target, ToolRegistry, ToolSpec},
+    core::task::{stream, Stream},
+};
+use flate2::read::GzDecoder;
+use reqwest::{
+    header::{CONTENT_RANGE, RANGE},
+    StatusCode,
+};
 use std::{
-    fs::{self, Permissions},
-    os::unix::fs::PermissionsExt,
-    path::PathBuf,
+    cmp::Ordering,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
 };
-use tokio::{fs::File, io::AsyncWriteExt};
 
 pub type ToolResult<T> = std::result::Result<T, ToolError>;
 
@@ -22,134 +34,492 @@ impl From<anyhow::Error> for ToolError {
     }
 }
 
-pub enum Tool {
-    Tailwind,
+/// Tool resolved from [`ToolSpec`] for a version and the current target
+#[derive(Clone)]
+pub struct Tool {
+    spec: Arc<dyn ToolSpec>,
+    version: String,
+    target: Target,
+    skip_checksum: bool,
+    locked_checksum: Option<Checksum>,
+}
+
+/// Download progress of a tool
+#[derive(Debug, Clone)]
+pub struct InstallProgress {
+    pub name: &'static str,
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub finished: bool,
+}
+
+// Only one state is alive per install, so the size of the downloading state does not matter
+#[allow(clippy::large_enum_variant)]
+enum InstallState {
+    Start(Tool),
+    Downloading(Tool, File, reqwest::Response, AssetHasher, InstallProgress),
+    Finished,
+}
+
+impl InstallState {
+    async fn next(self) -> ToolResult<Option<(InstallProgress, Self)>> {
+        match self {
+            InstallState::Start(tool) => {
+                if tool.is_installed() {
+                    Err(anyhow!("{tool} is already installed"))?
+                }
+
+                Tool::ensure_dir(&tool.version_path())?;
+                let (file, res, hasher, downloaded) = tool.start_download().await?;
+
+                let progress = InstallProgress {
+                    name: tool.name(),
+                    version: tool.version.clone(),
+                    downloaded,
+                    total: res.content_length().map(|len| downloaded + len),
+                    finished: false,
+                };
+
+                Ok(Some((
+                    progress.clone(),
+                    InstallState::Downloading(tool, file, res, hasher, progress),
+                )))
+            }
+            InstallState::Downloading(tool, mut file, mut res, mut hasher, mut progress) => {
+                match res
+                    .chunk()
+                    .await
+                    .context("Failed to stream chunks of downloading content")?
+                {
+                    Some(chunk) => {
+                        file.write_all(chunk.as_ref())
+                            .await
+                            .context("Failed to write chunks of downloading content")?;
+                        hasher.update(&chunk);
+                        progress.downloaded += chunk.len() as u64;
+
+                        Ok(Some((
+                            progress.clone(),
+                            InstallState::Downloading(tool, file, res, hasher, progress),
+                        )))
+                    }
+                    None => {
+                        file.flush()
+                            .await
+                            .context("Failed to flush downloaded content")?;
+                        drop(file);
+
+                        let digest = hasher.finalize();
+                        if let Err(e) = tool.verify(&digest).await {
+                            let _ = fs::remove_file(tool.download_path());
+                            Err(e)?
+                        }
+                        fs::write(tool.checksum_path(), &digest.sha256)
+                            .context("Failed to record checksum")?;
+
+                        tokio::task::spawn_blocking(move || tool.unpack())
+                            .await
+                            .context("Failed to join unpack task")??;
+
+                        progress.finished = true;
+                        Ok(Some((progress, InstallState::Finished)))
+                    }
+                }
+            }
+            InstallState::Finished => Ok(None),
+        }
+    }
 }
 
 impl Tool {
-    pub fn from_str(value: &str) -> Option<Self> {
-        match value {
-            "tailwindcss" => Some(Self::Tailwind),
-            _ => None,
+    pub fn new(spec: Arc<dyn ToolSpec>, version: impl Into<String>) -> ToolResult<Self> {
+        let tool = Self {
+            spec,
+            version: version.into(),
+            target: Target::current()?,
+            skip_checksum: false,
+            locked_checksum: None,
+        };
+
+        if tool.spec.asset_name(&tool.version, &tool.target).is_none() {
+            Err(anyhow!(
+                "{} is not available for {}-{}",
+                tool.name(),
+                tool.target.platform.as_str(),
+                tool.target.arch.as_str()
+            ))?
+        }
+
+        Ok(tool)
+    }
+
+    /// Parse `name` or `name@version`. Uses default version of the tool when version is
+    /// omitted.
+    pub fn parse(registry: &ToolRegistry, s: &str) -> ToolResult<Self> {
+        let (name, version) = match s.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (s, None),
+        };
+        let spec = registry
+            .get(name)
+            .with_context(|| format!("Could not find tool: {name}"))?;
+        let version = match version {
+            Some(version) => spec.normalize_version(version),
+            None => spec
+                .default_version()
+                .with_context(|| format!("Specify version of {name}: {name}@<version>"))?
+                .to_string(),
+        };
+
+        Self::new(spec, version)
+    }
+
+    /// Versions of the tool found on disk, oldest first
+    pub fn installed(spec: Arc<dyn ToolSpec>) -> ToolResult<Vec<Self>> {
+        let tool_path = Self::install_path().join(spec.name());
+        if !tool_path.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut versions = fs::read_dir(&tool_path)
+            .context("Failed to read install directory")?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+        versions.sort_by(|a, b| Self::compare_versions(a, b));
+
+        let mut tools = vec![];
+        for version in versions {
+            let tool = Self::new(spec.clone(), version)?;
+            if tool.is_installed() {
+                tools.push(tool);
+            }
+        }
+
+        Ok(tools)
+    }
+
+    /// Install even if no checksum is known for the asset. Mismatching checksum is still
+    /// rejected.
+    pub fn skip_checksum(mut self, skip: bool) -> Self {
+        self.skip_checksum = skip;
+        self
+    }
+
+    /// Expect digest recorded in lockfile. Takes precedence over checksums published for the
+    /// tool.
+    pub fn locked_checksum(mut self, checksum: Option<String>) -> Self {
+        self.locked_checksum = checksum.as_deref().map(Checksum::sha256);
+        self
+    }
+
+    /// SHA-256 digest of the asset this version was installed from
+    pub fn installed_checksum(&self) -> Option<String> {
+        fs::read_to_string(self.checksum_path())
+            .ok()
+            .map(|checksum| checksum.trim().to_string())
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.spec.name()
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Whether this version is picked by `dip tool use`
+    pub fn is_active(&self) -> bool {
+        fs::read_to_string(self.active_version_path())
+            .map(|version| version.trim() == self.version)
+            .unwrap_or(false)
+    }
+
+    /// Make this version the active one. The version must be installed.
+    pub fn activate(&self) -> ToolResult<()> {
+        if !self.is_installed() {
+            Err(anyhow!("{self} is not installed"))?
         }
+
+        fs::write(self.active_version_path(), &self.version)
+            .context("Failed to write active version")?;
+
+        Ok(())
     }
 
-    pub fn list() -> Vec<&'static str> {
-        vec!["tailwindcss"]
+    fn asset_name(&self) -> String {
+        self.spec
+            .asset_name(&self.version, &self.target)
+            .unwrap_or_else(|| self.name().to_string())
     }
 
+    // Paths are only computed here. Directories are created when a version is installed.
     fn app_path() -> PathBuf {
-        let p = dirs::home_dir().unwrap().join(".dip");
-        Self::ensure_dir(&p);
-        p
+        dirs::home_dir().unwrap().join(".dip")
     }
 
     fn install_path() -> PathBuf {
-        let p = Self::app_path().join("installs");
-        Self::ensure_dir(&p);
-        p
+        Self::app_path().join("installs")
+    }
+
+    fn tool_path(&self) -> PathBuf {
+        Self::install_path().join(self.name())
+    }
+
+    // Holds version selected by `dip tool use`
+    fn active_version_path(&self) -> PathBuf {
+        self.tool_path().join("active")
+    }
+
+    fn version_path(&self) -> PathBuf {
+        self.tool_path().join(&self.version)
     }
 
     fn bin_path(&self) -> PathBuf {
-        let p = Self::install_path().join(self.name());
-        Self::ensure_dir(&p);
-        p.join(self.bin_name())
+        self.version_path().join(self.spec.bin_name(&self.target))
     }
 
-    fn is_installed(&self) -> bool {
-        self.bin_path().is_file()
+    // Incomplete download. Kept on failure so that the next attempt can resume it.
+    fn download_path(&self) -> PathBuf {
+        self.version_path()
+            .join(format!("{}.part", self.asset_name()))
     }
 
-    pub async fn install(&self) -> ToolResult<()> {
-        if self.is_installed() {
-            Err(anyhow!("{} is already installed", self.name()))?
-        } else {
-            let mut file = File::create(self.bin_path())
-                .await
-                .context("Failed to create download target file")?;
+    // Binary extracted from archive, before it is moved to `bin_path`
+    fn staging_path(&self) -> PathBuf {
+        self.version_path()
+            .join(format!("{}.tmp", self.spec.bin_name(&self.target)))
+    }
 
-            file.set_permissions(Permissions::from_mode(0o777))
-                .await
-                .context("Failed to give permission to download target file")?;
+    fn checksum_path(&self) -> PathBuf {
+        self.version_path().join("sha256")
+    }
 
-            let mut res = reqwest::get(self.download_url())
+    pub fn download_url(&self) -> ToolResult<String> {
+        let url = self
+            .spec
+            .download_url(&self.version, &self.target)
+            .with_context(|| format!("Could not find download url of {}", self.name()))?;
+
+        Ok(url)
+    }
+
+    // Resume partial download with HTTP range request if server supports it. Returns file to
+    // append to, response, hasher fed with already downloaded bytes, and their length.
+    async fn start_download(&self) -> ToolResult<(File, reqwest::Response, AssetHasher, u64)> {
+        let path = self.download_path();
+        let url = self.download_url()?;
+        let client = reqwest::Client::new();
+
+        let resume_from = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if resume_from > 0 {
+            let res = client
+                .get(&url)
+                .header(RANGE, format!("bytes={resume_from}-"))
+                .send()
                 .await
                 .with_context(|| format!("Failed to download tool: {}", self.name()))?;
 
-            while let Some(chunk) = res
-                .chunk()
-                .await
-                .context("Failed to stream chunks of downloading content")?
-            {
-                file.write(chunk.as_ref())
+            let content_range = res
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok());
+            let resumed = res.status() == StatusCode::PARTIAL_CONTENT
+                && matches!(content_range, Some(v) if v.starts_with(&format!("bytes {resume_from}-")));
+
+            if resumed {
+                let mut hasher = AssetHasher::default();
+                hasher.update(
+                    tokio::fs::read(&path)
+                        .await
+                        .context("Failed to read partial download")?,
+                );
+                let file = OpenOptions::new()
+                    .append(true)
+                    .open(&path)
                     .await
-                    .context("Failed to write chunks of downloading content")?;
+                    .context("Failed to open partial download")?;
+
+                return Ok((file, res, hasher, resume_from));
             }
 
-            Ok(())
+            // Server ignored range, send whole content instead
+            if res.status().is_success() {
+                let file = File::create(&path)
+                    .await
+                    .context("Failed to create download target file")?;
+
+                return Ok((file, res, AssetHasher::default(), 0));
+            }
         }
+
+        let res = client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .with_context(|| format!("Failed to download tool: {}", self.name()))?;
+        let file = File::create(&path)
+            .await
+            .context("Failed to create download target file")?;
+
+        Ok((file, res, AssetHasher::default(), 0))
     }
 
-    fn target_platform(&self) -> Platform {
-        match self {
-            _ => match std::env::consts::OS {
-                "linux" => Platform::Linux,
-                "macos" => Platform::Macos,
-                "windows" => Platform::Windows,
-                _ => panic!("unsupported platformm"),
-            },
+    // Locked or pinned digest takes precedence over published checksum. `None` if the release
+    // does not publish one.
+    async fn expected_checksum(&self) -> ToolResult<Option<Checksum>> {
+        if let Some(checksum) = &self.locked_checksum {
+            return Ok(Some(checksum.clone()));
+        }
+        if let Some(checksum) = self.spec.checksum(&self.version, &self.target) {
+            return Ok(Some(checksum));
         }
-    }
 
-    fn target_arch(&self) -> &'static str {
-        match std::env::consts::ARCH {
-            "x86" | "x86_64" => "x64",
-            "arm" | "aarch64" => "arm64",
-            _ => panic!("unsupported arch"),
+        let url = match self.spec.checksum_url(&self.version, &self.target) {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        let res = reqwest::get(&url)
+            .await
+            .with_context(|| format!("Failed to download checksum file: {url}"))?;
+        // Release does not publish checksum file
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
         }
+        let content = res
+            .error_for_status()
+            .with_context(|| format!("Failed to download checksum file: {url}"))?
+            .text()
+            .await
+            .context("Failed to read checksum file")?;
+
+        let asset_name = self.asset_name();
+        let checksum = self
+            .spec
+            .parse_checksum(&content, &asset_name)
+            .with_context(|| format!("Could not find checksum of {asset_name} in {url}"))?;
+
+        Ok(Some(checksum))
     }
 
-    fn name(&self) -> &'static str {
-        match self {
-            Tool::Tailwind => "tailwindcss",
+    async fn verify(&self, digest: &AssetDigest) -> ToolResult<()> {
+        match self.expected_checksum().await? {
+            Some(expected) => Ok(expected.verify(&self.asset_name(), digest)?),
+            None if self.skip_checksum => Ok(()),
+            None => Err(anyhow!(
+                "No checksum is known for {}. Pass --skip-checksum to install it anyway.",
+                self.asset_name()
+            ))?,
         }
     }
 
-    fn version(&self) -> &'static str {
-        match self {
-            Tool::Tailwind => "v3.1.8",
-        }
+    pub fn is_installed(&self) -> bool {
+        self.bin_path().is_file()
+    }
+
+    /// Download tool binary. Yields progress every time a chunk is written.
+    pub fn install(self) -> impl Stream<Item = ToolResult<InstallProgress>> {
+        stream::try_unfold(InstallState::Start(self), InstallState::next)
     }
 
-    fn bin_name(&self) -> String {
-        let windows_extension = match self.target_platform() {
-            Platform::Windows => ".exe",
-            _ => "",
+    // Place executable from downloaded asset and run post install hook. Executable is renamed
+    // into `bin_path` only when it is complete, so partial installs are never visible.
+    fn unpack(&self) -> ToolResult<()> {
+        let download_path = self.download_path();
+        let bin_path = self.bin_path();
+
+        let staged_path = match self.spec.archive_type() {
+            ArchiveType::Binary => download_path,
+            ArchiveType::TarGz => {
+                let staging_path = self.staging_path();
+                let member = self.spec.archive_member(&self.version, &self.target);
+                let file =
+                    fs::File::open(&download_path).context("Failed to open downloaded archive")?;
+                let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+                let mut found = false;
+                for entry in archive.entries().context("Failed to read archive")? {
+                    let mut entry = entry.context("Failed to read archive entry")?;
+                    if entry.path().context("Invalid archive entry path")? == Path::new(&member) {
+                        entry
+                            .unpack(&staging_path)
+                            .context("Failed to unpack binary from archive")?;
+                        found = true;
+                        break;
+                    }
+                }
+
+                fs::remove_file(&download_path).context("Failed to remove downloaded archive")?;
+                if !found {
+                    Err(anyhow!("Could not find {member} in downloaded archive"))?
+                }
+                staging_path
+            }
         };
 
-        match self {
-            Tool::Tailwind => format!(
-                "tailwindcss-{target}-{arch}{optional_ext}",
-                target = self.target_platform().as_str(),
-                arch = self.target_arch(),
-                optional_ext = windows_extension
-            ),
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&staged_path, fs::Permissions::from_mode(0o755))
+                .context("Failed to give permission to installed binary")?;
+        }
+        fs::rename(&staged_path, &bin_path).context("Failed to move binary into place")?;
+
+        if let Err(e) = self.spec.post_install(&bin_path) {
+            let _ = fs::remove_file(&bin_path);
+            Err(e)?
         }
+
+        // First installed version becomes active
+        if !self.active_version_path().is_file() {
+            self.activate()?;
+        }
+
+        Ok(())
     }
 
-    fn download_url(&self) -> String {
-        match self {
-            Tool::Tailwind => format!(
-                "https://github.com/tailwindlabs/tailwindcss/releases/download/{version}/{bin_name}",
-                version = self.version(),
-                bin_name = self.bin_name(),
-            ),
+    fn ensure_dir(p: &Path) -> ToolResult<()> {
+        if !p.is_dir() {
+            fs::create_dir_all(p)
+                .with_context(|| format!("Failed to create directory: {}", p.display()))?;
         }
+
+        Ok(())
     }
 
-    fn ensure_dir(p: &PathBuf) {
-        if !&p.is_dir() {
-            fs::create_dir_all(&p).unwrap();
+    // Orders `1.10.0` after `1.9.0` and `1.0.0-beta` before `1.0.0`. Leading `v` or
+    // `version_` is ignored, and parts that are not numbers are compared as text.
+    fn compare_versions(a: &str, b: &str) -> Ordering {
+        fn key(version: &str) -> (Vec<Result<u64, &str>>, bool, Option<&str>) {
+            let version = version
+                .trim_start_matches("version_")
+                .trim_start_matches('v');
+            let version = version.split('+').next().unwrap_or(version);
+            let (core, pre) = match version.split_once('-') {
+                Some((core, pre)) => (core, Some(pre)),
+                None => (version, None),
+            };
+            let core = core
+                .split('.')
+                .map(|part| part.parse::<u64>().map_err(|_| part))
+                .collect();
+
+            (core, pre.is_none(), pre)
         }
+
+        key(a).cmp(&key(b))
+    }
+}
+
+impl std::fmt::Display for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name(), self.version)
     }
 }